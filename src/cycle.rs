@@ -0,0 +1,53 @@
+use hashbrown::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// every state up to the first repeat, the cycle is states[start..start + length]
+pub struct Cycle<T> {
+    pub start: usize,
+    pub length: usize,
+    states: Vec<T>,
+}
+
+impl<T> Cycle<T> {
+    pub fn get(&self, step: usize) -> &T {
+        if step < self.start {
+            &self.states[step]
+        } else {
+            &self.states[self.start + (step - self.start) % self.length]
+        }
+    }
+}
+
+fn get_hash<T: Hash>(state: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+// steps from the initial state until one repeats, states are only compared when their hashes match
+pub fn find_cycle<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: Hash + Eq,
+    F: FnMut(&T) -> T,
+{
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut states = vec![initial];
+    loop {
+        let index = states.len() - 1;
+        let hash = get_hash(&states[index]);
+        let candidates = seen.entry(hash).or_insert_with(Vec::new);
+        if let Some(start) = candidates.iter().find(|i| states[**i] == states[index]) {
+            let start = *start;
+            states.pop();
+            return Cycle {
+                start,
+                length: index - start,
+                states,
+            };
+        }
+        candidates.push(index);
+        let next = step(&states[index]);
+        states.push(next);
+    }
+}
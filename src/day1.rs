@@ -1,4 +1,66 @@
-use hashbrown::HashSet;
+use itertools::Itertools;
+
+fn get_changes(input: &str) -> Vec<isize> {
+    input
+        .split('\n')
+        .filter_map(|s| s.parse::<isize>().ok())
+        .collect()
+}
+
+// frequencies before each change of the first pass, and how far every pass moves them
+fn get_frequencies(changes: &[isize]) -> (Vec<isize>, isize) {
+    let mut frequencies = Vec::with_capacity(changes.len());
+    let mut drift = 0;
+    for change in changes {
+        frequencies.push(drift);
+        drift += change;
+    }
+    (frequencies, drift)
+}
+
+// Frequency i of pass k is frequencies[i] + k * drift, so only frequencies in the same
+// residue class modulo the drift can meet, and the first to do so are neighbours once
+// each class is sorted in the direction of the drift.
+// Returns the (time, frequency) of each candidate repeat.
+fn get_repeats(changes: &[isize]) -> Vec<(usize, isize)> {
+    let (frequencies, drift) = get_frequencies(changes);
+    let (sign, step) = (if drift < 0 { -1 } else { 1 }, drift.abs());
+    let residue = |f: isize| if step == 0 { 0 } else { f.rem_euclid(step) };
+    let sorted = (0..frequencies.len())
+        .map(|i| (residue(frequencies[i] * sign), frequencies[i] * sign, i))
+        .sorted();
+    let mut repeats = Vec::new();
+    for (a, b) in sorted.into_iter().tuple_windows() {
+        if a.0 != b.0 {
+            continue;
+        }
+        if a.1 == b.1 {
+            // seen twice in the first pass
+            repeats.push((b.2, frequencies[b.2]));
+        } else if step != 0 {
+            repeats.push((
+                a.2 + changes.len() * ((b.1 - a.1) / step) as usize,
+                frequencies[b.2],
+            ));
+        }
+    }
+    if step == 0 && !changes.is_empty() {
+        // back to the start after one pass
+        repeats.push((changes.len(), 0));
+    }
+    repeats
+}
+
+pub fn find_first_repeat(changes: &[isize]) -> Option<isize> {
+    let mut first: Option<(usize, isize)> = None;
+    for repeat in get_repeats(changes) {
+        match first {
+            Some((time, _)) if time <= repeat.0 => (),
+            _ => first = Some(repeat),
+        }
+    }
+    first.map(|(_, frequency)| frequency)
+}
 
 #[aoc(day1, part1)]
 pub fn solve_day1_part1(input: &str) -> isize {
@@ -10,34 +72,14 @@ pub fn solve_day1_part1(input: &str) -> isize {
 
 #[aoc(day1, part2)]
 pub fn solve_day1_part2(input: &str) -> isize {
-    let input_list: Vec<_> = input
-        .split('\n')
-        .filter_map(|s| s.parse::<isize>().ok())
-        .collect();
-    let (mut frequencies, mut current_frequency) = (HashSet::new(), 0);
-    frequencies.insert(current_frequency);
-    loop {
-        for i in &input_list {
-            current_frequency += *i;
-            if frequencies.contains(&current_frequency) {
-                return current_frequency;
-            }
-            frequencies.insert(current_frequency);
-        }
-    }
+    find_first_repeat(&get_changes(input)).expect("Frequency never repeats")
 }
 
 #[aoc(day1, part2, iterator)]
 pub fn solve_day1_part2_iterator(input: &str) -> isize {
-    let input_list: Vec<_> = input
-        .split('\n')
-        .filter_map(|s| s.parse::<isize>().ok())
-        .collect();
-    let (mut frequencies, mut current_frequency) = (HashSet::new(), 0);
-    frequencies.insert(current_frequency);
-    input_list.into_iter().cycle().find(|c| {
-        current_frequency += c;
-        !frequencies.insert(current_frequency) // insert returns false if key exists
-    });
-    current_frequency
+    get_repeats(&get_changes(input))
+        .into_iter()
+        .min_by_key(|(time, _)| *time)
+        .map(|(_, frequency)| frequency)
+        .expect("Frequency never repeats")
 }
@@ -1,14 +1,49 @@
-use hashbrown::{HashMap, HashSet};
-use itertools::Itertools;
+use hashbrown::HashMap;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 pub struct CartSystem {
     num_rows: usize,
     num_cols: usize,
-    tracks: HashMap<(usize, usize), Track>,
-    carts: Vec<(usize, usize, Direction, u8)>,
+    tracks: Vec<Vec<Track>>,
+    carts: Vec<Cart>,
+    num_ticks: usize,
+    collisions: Vec<Collision>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Cart {
+    pub id: usize,
+    pub row: usize,
+    pub column: usize,
+    pub direction: Direction,
+    turn: u8,
+}
+
+pub enum ReplayOutput {
+    Terminal(Duration),
+    Files(PathBuf),
+}
+
+pub struct Viewport {
+    pub row: usize,
+    pub column: usize,
+    pub radius: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Collision {
+    pub tick: usize,
+    pub row: usize,
+    pub column: usize,
+    pub cart_ids: (usize, usize),
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -112,246 +147,435 @@ impl FromStr for Direction {
     }
 }
 
-impl CartSystem {
-    fn new(input: &str) -> CartSystem {
-        let (num_rows, num_cols) = (
-            input.split('\n').count(),
-            input.split('\n').map(|l| l.len()).max().unwrap(),
-        );
-        let mut tracks = HashMap::with_capacity(num_rows * num_cols);
-        let mut carts = Vec::new();
-        for (i, row) in input.split('\n').enumerate() {
-            let mut new_row = if i == 0 {
-                (0..26).map(|_| ' ').collect::<String>()
-            } else {
-                String::new()
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+// candidate sets of a cell known to be empty and of one nothing is known about yet
+const EMPTY: u16 = 1 << 0;
+const ANY: u16 = 0xffff;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrackError {
+    UnknownPiece {
+        row: usize,
+        column: usize,
+        piece: char,
+    },
+    Disconnected {
+        row: usize,
+        column: usize,
+    },
+    Ambiguous {
+        row: usize,
+        column: usize,
+    },
+    AmbiguousFirstRow,
+}
+
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrackError::UnknownPiece { row, column, piece } => {
+                write!(f, "Unknown piece {:?} at {},{}", piece, column, row)
+            }
+            TrackError::Disconnected { row, column } => {
+                write!(
+                    f,
+                    "Track at {},{} doesn't connect to its neighbours",
+                    column, row
+                )
+            }
+            TrackError::Ambiguous { row, column } => {
+                write!(
+                    f,
+                    "Track at {},{} could be more than one piece",
+                    column, row
+                )
+            }
+            TrackError::AmbiguousFirstRow => {
+                write!(f, "Can't tell how much whitespace the first row lost")
+            }
+        }
+    }
+}
+
+impl Error for TrackError {}
+
+// bitsets over the connection masks a piece could have
+fn get_candidates(piece: char) -> Option<u16> {
+    let masks: &[u8] = match piece {
+        ' ' => &[0],
+        '-' => &[EAST | WEST],
+        '|' => &[NORTH | SOUTH],
+        '+' => &[NORTH | EAST | SOUTH | WEST],
+        '/' => &[SOUTH | EAST, NORTH | WEST],
+        '\\' => &[SOUTH | WEST, NORTH | EAST],
+        '<' | '>' | '^' | 'v' => {
+            let direction = match piece {
+                '<' => WEST,
+                '>' => EAST,
+                '^' => NORTH,
+                _ => SOUTH,
             };
-            new_row.push_str(row);
-            for (j, c) in new_row.chars().enumerate() {
-                tracks.insert((i, j), c.to_string().parse().unwrap());
-                if "<>^v".contains(c) {
-                    carts.push((i, j, c.to_string().parse().unwrap(), 1));
+            return Some(
+                [
+                    EAST | WEST,
+                    NORTH | SOUTH,
+                    NORTH | EAST | SOUTH | WEST,
+                    SOUTH | EAST,
+                    NORTH | WEST,
+                    SOUTH | WEST,
+                    NORTH | EAST,
+                ]
+                .iter()
+                .filter(|mask| *mask & direction != 0)
+                .fold(0, |set, mask| set | 1 << mask),
+            );
+        }
+        _ => return None,
+    };
+    Some(masks.iter().fold(0, |set, mask| set | 1 << mask))
+}
+
+fn get_track(mask: u8) -> Track {
+    match mask {
+        0 => Track::Empty,
+        m if m == EAST | WEST => Track::Horizontal,
+        m if m == NORTH | SOUTH => Track::Vertical,
+        m if m == SOUTH | EAST || m == NORTH | WEST => Track::CurveForward,
+        m if m == SOUTH | WEST || m == NORTH | EAST => Track::CurveBackward,
+        _ => Track::Intersection,
+    }
+}
+
+// keeps the candidates of one cell that agree with at least one candidate of its neighbour
+fn restrict(candidates: u16, neighbor: u16, towards: u8, back: u8) -> u16 {
+    let neighbor_connects = |connected: bool| {
+        (0..16).any(|m: u8| neighbor & 1 << m != 0 && (m & back != 0) == connected)
+    };
+    (0..16)
+        .filter(|m: &u8| candidates & 1 << m != 0 && neighbor_connects(m & towards != 0))
+        .fold(0, |set, m| set | 1 << m)
+}
+
+fn get_row_candidates(
+    row: usize,
+    pieces: &[char],
+    num_columns: usize,
+) -> Result<Vec<u16>, TrackError> {
+    let mut candidates = vec![EMPTY; num_columns];
+    for (column, piece) in pieces.iter().enumerate() {
+        candidates[column] = get_candidates(*piece).ok_or(TrackError::UnknownPiece {
+            row,
+            column,
+            piece: *piece,
+        })?;
+    }
+    Ok(candidates)
+}
+
+// Restricts the queued cells against their neighbours, and queues the neighbours of every
+// cell that changed. Rows above first_row are left alone.
+fn propagate(
+    grid: &mut [Vec<u16>],
+    mut queue: VecDeque<(usize, usize)>,
+    first_row: usize,
+) -> Result<(), TrackError> {
+    let num_columns = grid.first().map_or(0, |r| r.len());
+    let mut queued = vec![vec![false; num_columns]; grid.len()];
+    for (row, column) in &queue {
+        queued[*row][*column] = true;
+    }
+    while let Some((row, column)) = queue.pop_front() {
+        queued[row][column] = false;
+        let neighbors = [
+            (row.checked_sub(1).map(|r| (r, column)), NORTH, SOUTH),
+            (Some((row, column + 1)), EAST, WEST),
+            (Some((row + 1, column)), SOUTH, NORTH),
+            (column.checked_sub(1).map(|c| (row, c)), WEST, EAST),
+        ];
+        let mut candidates = grid[row][column];
+        for (neighbor, towards, back) in neighbors.iter() {
+            let neighbor_candidates = neighbor
+                .and_then(|(r, c)| grid.get(r).and_then(|r| r.get(c)).cloned())
+                .unwrap_or(EMPTY);
+            candidates = restrict(candidates, neighbor_candidates, *towards, *back);
+        }
+        if candidates == grid[row][column] {
+            continue;
+        }
+        if candidates == 0 {
+            return Err(TrackError::Disconnected { row, column });
+        }
+        grid[row][column] = candidates;
+        for (neighbor, _, _) in neighbors.iter() {
+            if let Some((r, c)) = *neighbor {
+                if r >= first_row && r < grid.len() && c < num_columns && !queued[r][c] {
+                    queued[r][c] = true;
+                    queue.push_back((r, c));
                 }
             }
         }
-        let mut cart_system = CartSystem {
-            num_rows,
-            num_cols,
-            tracks,
-            carts,
-        };
-        cart_system.smooth();
-        cart_system
     }
+    Ok(())
+}
 
-    fn smooth(&mut self) {
-        for (row, column, direction, _) in self.carts.iter() {
-            let (row, column) = (*row, *column);
-            match *direction {
-                Direction::Left => {
-                    if let Some(Track::Vertical) = self.tracks.get(&(row + 1, column)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveBackward;
-                    }
-                    if let Some(Track::Vertical) = self.tracks.get(&(row - 1, column)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveForward;
-                    }
-                }
-                Direction::Up => {
-                    if let Some(Track::Horizontal) = self.tracks.get(&(row, column + 1)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveBackward;
-                    }
-                    if let Some(Track::Horizontal) = self.tracks.get(&(row, column - 1)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveForward;
-                    }
-                }
-                Direction::Down => {
-                    if let Some(Track::Horizontal) = self.tracks.get(&(row, column + 1)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveForward;
-                    }
-                    if let Some(Track::Horizontal) = self.tracks.get(&(row, column - 1)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveBackward;
+fn get_tracks(grid: Vec<Vec<u16>>) -> Result<Vec<Vec<Track>>, TrackError> {
+    let mut tracks = Vec::with_capacity(grid.len());
+    for (row, candidates) in grid.into_iter().enumerate() {
+        let mut track_row = Vec::with_capacity(candidates.len());
+        for (column, candidate) in candidates.into_iter().enumerate() {
+            match candidate.count_ones() {
+                0 => return Err(TrackError::Disconnected { row, column }),
+                1 => track_row.push(get_track(candidate.trailing_zeros() as u8)),
+                _ => return Err(TrackError::Ambiguous { row, column }),
+            }
+        }
+        tracks.push(track_row);
+    }
+    Ok(tracks)
+}
+
+impl CartSystem {
+    // The aoc runner trims the input, which strips the leading spaces of the first row.
+    // The other rows are solved once with nothing known about the first, then each left
+    // padding that could restore the first row is checked against them. More than one
+    // padding giving a connected track means the input is ambiguous.
+    pub fn parse(input: &str) -> Result<CartSystem, TrackError> {
+        let mut rows: Vec<Vec<char>> = input
+            .trim_end_matches('\n')
+            .split('\n')
+            .map(|l| l.chars().collect())
+            .collect();
+        let num_columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let mut grid = vec![vec![ANY; num_columns]];
+        for (row, pieces) in rows.iter().enumerate().skip(1) {
+            grid.push(get_row_candidates(row, pieces, num_columns)?);
+        }
+        propagate(
+            &mut grid,
+            iproduct!(1..rows.len(), 0..num_columns).collect(),
+            1,
+        )?;
+        let first_row = rows[0].clone();
+        let mut solution = None;
+        let mut first_error = None;
+        for padding in 0..=num_columns - first_row.len() {
+            let padded: Vec<char> = (0..padding).map(|_| ' ').chain(first_row.clone()).collect();
+            let mut candidates = grid.clone();
+            candidates[0] = get_row_candidates(0, &padded, num_columns)?;
+            // the first row and the row below it are the only cells that saw the change
+            let queue = iproduct!(0..rows.len().min(2), 0..num_columns).collect();
+            match propagate(&mut candidates, queue, 0).and_then(|_| get_tracks(candidates)) {
+                Ok(tracks) => {
+                    if solution.is_some() {
+                        return Err(TrackError::AmbiguousFirstRow);
                     }
+                    solution = Some((padded, tracks));
                 }
-                Direction::Right => {
-                    if let Some(Track::Vertical) = self.tracks.get(&(row + 1, column)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveForward;
-                    }
-                    if let Some(Track::Vertical) = self.tracks.get(&(row - 1, column)) {
-                        *self.tracks.entry((row, column)).or_insert(Track::Empty) =
-                            Track::CurveBackward;
-                    }
+                Err(error) => first_error = first_error.or(Some(error)),
+            }
+        }
+        let tracks = match solution {
+            Some((padded, tracks)) => {
+                rows[0] = padded;
+                tracks
+            }
+            None => return Err(first_error.unwrap()),
+        };
+        let mut carts = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            for (j, c) in row.iter().enumerate() {
+                if "<>^v".contains(*c) {
+                    carts.push(Cart {
+                        id: carts.len(),
+                        row: i,
+                        column: j,
+                        direction: c.to_string().parse().unwrap(),
+                        turn: 1,
+                    });
                 }
             }
         }
+        Ok(CartSystem {
+            num_rows: rows.len(),
+            num_cols: num_columns,
+            tracks,
+            carts,
+            num_ticks: 0,
+            collisions: Vec::new(),
+        })
     }
-    #[allow(dead_code)]
-    fn display(&self, show_tracks: bool, show_carts: bool) {
-        let mut track_string = (0..self.num_rows)
+
+    // one character per cell, crashes from the latest tick are drawn as X (in red on a terminal)
+    pub fn render(&self, viewport: Option<&Viewport>, colour: bool) -> String {
+        let (rows, columns) = match viewport {
+            Some(v) => (
+                v.row.saturating_sub(v.radius)..(v.row + v.radius + 1).min(self.num_rows),
+                v.column.saturating_sub(v.radius)..(v.column + v.radius + 1).min(self.num_cols),
+            ),
+            None => (0..self.num_rows, 0..self.num_cols),
+        };
+        let mut cells: Vec<Vec<String>> = rows
+            .clone()
             .map(|r| {
-                (0..self.num_cols)
-                    .map(|c| {
-                        if show_tracks {
-                            format!("{}", self.tracks[&(r, c)]).chars().next().unwrap()
-                        } else {
-                            ' '
-                        }
-                    })
-                    .collect::<Vec<_>>()
+                columns
+                    .clone()
+                    .map(|c| self.tracks[r][c].to_string())
+                    .collect()
             })
-            .collect::<Vec<_>>();
-        if show_carts {
-            for (row, col, dir, _) in &self.carts {
-                track_string[*row][*col] = format!("{}", dir).chars().next().unwrap();
+            .collect();
+        let mut draw = |row: usize, column: usize, cell: String| {
+            if rows.contains(&row) && columns.contains(&column) {
+                cells[row - rows.start][column - columns.start] = cell;
             }
+        };
+        for cart in &self.carts {
+            draw(cart.row, cart.column, cart.direction.to_string());
         }
-        println!(
-            "{}",
-            track_string
-                .into_iter()
-                .map(|v| v.into_iter().collect::<String>())
-                .collect::<Vec<_>>()
-                .join("\n")
-        );
+        for collision in self.collisions.iter().rev() {
+            if collision.tick != self.num_ticks {
+                break;
+            }
+            let crash = if colour { "\x1b[1;31mX\x1b[0m" } else { "X" };
+            draw(collision.row, collision.column, crash.to_string());
+        }
+        cells.into_iter().map(|row| row.concat() + "\n").collect()
     }
 
-    fn move_cart(&mut self, index: usize) {
-        let (row, column, direction, turn) = self.carts[index];
-        let mut turned = false;
-        let (new_row, new_column, new_direction) = match direction {
-            Direction::Left => match self.tracks.get(&(row, column - 1)) {
-                Some(Track::Horizontal) => (row, column - 1, Direction::Left),
-                Some(Track::CurveForward) => (row, column - 1, Direction::Down),
-                Some(Track::CurveBackward) => (row, column - 1, Direction::Up),
-                Some(Track::Intersection) => {
-                    turned = true;
-                    (row, column - 1, direction.turn(turn))
-                }
-                _ => panic!("Weird cart, -|"),
-            },
-            Direction::Up => match self.tracks.get(&(row - 1, column)) {
-                Some(Track::Vertical) => (row - 1, column, Direction::Up),
-                Some(Track::CurveForward) => (row - 1, column, Direction::Right),
-                Some(Track::CurveBackward) => (row - 1, column, Direction::Left),
-                Some(Track::Intersection) => {
-                    turned = true;
-                    (row - 1, column, direction.turn(turn))
-                }
-                _ => panic!("Weird cart, |-"),
-            },
-            Direction::Down => match self.tracks.get(&(row + 1, column)) {
-                Some(Track::Vertical) => (row + 1, column, Direction::Down),
-                Some(Track::CurveForward) => (row + 1, column, Direction::Left),
-                Some(Track::CurveBackward) => (row + 1, column, Direction::Right),
-                Some(Track::Intersection) => {
-                    turned = true;
-                    (row + 1, column, direction.turn(turn))
+    // renders the starting state and every tick until at most one cart is left
+    pub fn replay(&mut self, output: &ReplayOutput, viewport: Option<&Viewport>) -> io::Result<()> {
+        if let ReplayOutput::Terminal(_) = output {
+            print!("\x1b[2J");
+        }
+        loop {
+            match output {
+                ReplayOutput::Terminal(delay) => {
+                    println!(
+                        "\x1b[H{}tick {}",
+                        self.render(viewport, true),
+                        self.num_ticks
+                    );
+                    io::stdout().flush()?;
+                    thread::sleep(*delay);
                 }
-                _ => panic!("Weird cart, |-"),
-            },
-            Direction::Right => match self.tracks.get(&(row, column + 1)) {
-                Some(Track::Horizontal) => (row, column + 1, Direction::Right),
-                Some(Track::CurveForward) => (row, column + 1, Direction::Up),
-                Some(Track::CurveBackward) => (row, column + 1, Direction::Down),
-                Some(Track::Intersection) => {
-                    turned = true;
-                    (row, column + 1, direction.turn(turn))
+                ReplayOutput::Files(directory) => {
+                    let path = directory.join(format!("frame_{:06}.txt", self.num_ticks));
+                    File::create(path)?.write_all(self.render(viewport, false).as_bytes())?;
                 }
-                _ => panic!("Weird cart, -|"),
-            },
-        };
-        let mut new_turn = turn;
-        if turned {
-            new_turn += 1;
-            if new_turn > 3 {
-                new_turn = 1;
             }
+            if self.carts.len() <= 1 {
+                return Ok(());
+            }
+            self.tick();
         }
-        self.carts[index] = (new_row, new_column, new_direction, new_turn);
     }
 
-    fn tick(&mut self) -> Option<(usize, usize)> {
-        let mut positions: HashSet<_> = self.carts.iter().map(|x| (x.0, x.1)).collect();
-        let order: Vec<_> = self
+    fn move_cart(&mut self, index: usize) {
+        let cart = &mut self.carts[index];
+        match cart.direction {
+            Direction::Left => cart.column -= 1,
+            Direction::Up => cart.row -= 1,
+            Direction::Down => cart.row += 1,
+            Direction::Right => cart.column += 1,
+        }
+        cart.direction = match (self.tracks[cart.row][cart.column], cart.direction) {
+            (Track::Horizontal, _) | (Track::Vertical, _) => cart.direction,
+            (Track::CurveForward, Direction::Left) => Direction::Down,
+            (Track::CurveForward, Direction::Up) => Direction::Right,
+            (Track::CurveForward, Direction::Down) => Direction::Left,
+            (Track::CurveForward, Direction::Right) => Direction::Up,
+            (Track::CurveBackward, Direction::Left) => Direction::Up,
+            (Track::CurveBackward, Direction::Up) => Direction::Left,
+            (Track::CurveBackward, Direction::Down) => Direction::Right,
+            (Track::CurveBackward, Direction::Right) => Direction::Down,
+            (Track::Intersection, _) => {
+                let direction = cart.direction.turn(cart.turn);
+                cart.turn = cart.turn % 3 + 1;
+                direction
+            }
+            (Track::Empty, _) => panic!("Cart {} left the track", cart.id),
+        };
+    }
+
+    // moves every cart once, crashed carts are removed straight away
+    pub fn tick(&mut self) -> &[Collision] {
+        let num_collisions = self.collisions.len();
+        self.carts.sort_by_key(|cart| (cart.row, cart.column));
+        let mut positions: HashMap<_, _> = self
             .carts
             .iter()
             .enumerate()
-            .sorted_by(|a, b| ((a.1).0, (a.1).1).cmp(&((b.1).0, (b.1).1)))
-            .into_iter()
-            .map(|(i, _)| i)
+            .map(|(i, cart)| ((cart.row, cart.column), i))
             .collect();
-        for i in order {
-            let (old_row, old_column, _, _) = self.carts[i];
-            positions.remove(&(old_row, old_column));
+        let mut crashed = vec![false; self.carts.len()];
+        for i in 0..self.carts.len() {
+            if crashed[i] {
+                continue;
+            }
+            positions.remove(&(self.carts[i].row, self.carts[i].column));
             self.move_cart(i);
-            let (row, column, _, _) = self.carts[i];
-            if positions.contains(&(row, column)) {
-                return Some((row, column));
+            let position = (self.carts[i].row, self.carts[i].column);
+            match positions.remove(&position) {
+                Some(j) => {
+                    crashed[i] = true;
+                    crashed[j] = true;
+                    self.collisions.push(Collision {
+                        tick: self.num_ticks + 1,
+                        row: position.0,
+                        column: position.1,
+                        cart_ids: (self.carts[i].id, self.carts[j].id),
+                    });
+                }
+                None => {
+                    positions.insert(position, i);
+                }
             }
-            positions.insert((row, column));
         }
-        None
+        let mut crashed = crashed.into_iter();
+        self.carts.retain(|_| !crashed.next().unwrap());
+        self.num_ticks += 1;
+        &self.collisions[num_collisions..]
     }
 
-    fn tick_remove(&mut self) -> Option<(usize, usize)> {
-        if self.carts.len() == 1 {
-            return Some((self.carts[0].0, self.carts[0].1));
-        }
-        let mut positions = HashMap::new();
-        for i in 0..self.carts.len() {
-            positions.insert((self.carts[i].0, self.carts[i].1), i);
+    // runs until the k-th crash (counting from 1), or None if too few carts are left for it
+    pub fn get_crash(&mut self, k: usize) -> Option<Collision> {
+        while self.collisions.len() < k && self.carts.len() > 1 {
+            self.tick();
         }
-        let order: Vec<_> = self
-            .carts
-            .iter()
-            .enumerate()
-            .sorted_by(|a, b| ((a.1).0, (a.1).1).cmp(&((b.1).0, (b.1).1)))
-            .into_iter()
-            .map(|(i, _)| i)
-            .collect();
-        let mut remove_indices = HashSet::new();
-        for i in order {
-            let (old_row, old_column, _, _) = self.carts[i];
-            positions.remove(&(old_row, old_column));
-            self.move_cart(i);
-            let (row, column, _, _) = self.carts[i];
-            if positions.contains_key(&(row, column)) {
-                remove_indices.insert(i);
-                remove_indices.insert(positions.remove(&(row, column)).unwrap());
-            } else {
-                positions.insert((row, column), i);
-            }
+        self.collisions.get(k - 1).cloned()
+    }
+
+    // runs until at most one cart is left
+    pub fn run(&mut self) -> Option<&Cart> {
+        while self.carts.len() > 1 {
+            self.tick();
         }
-        self.carts = (0..self.carts.len())
-            .filter(|i| !remove_indices.contains(i))
-            .map(|i| self.carts[i])
-            .collect();
-        None
+        self.carts.first()
+    }
+
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
     }
 }
 
+pub fn simulate(input: &str) -> CartSystem {
+    let mut cart_system = CartSystem::parse(input).unwrap();
+    cart_system.run();
+    cart_system
+}
+
 #[aoc(day13, part1)]
 pub fn solve_day13_part1(input: &str) -> String {
-    let mut cart_system = CartSystem::new(input);
-    loop {
-        if let Some((row, column)) = cart_system.tick() {
-            return format!("{},{}", column, row);
-        }
-    }
+    let collision = CartSystem::parse(input).unwrap().get_crash(1).unwrap();
+    format!("{},{}", collision.column, collision.row)
 }
 
 #[aoc(day13, part2)]
 pub fn solve_day13_part2(input: &str) -> String {
-    let mut cart_system = CartSystem::new(input);
-    loop {
-        if let Some((row, column)) = cart_system.tick_remove() {
-            return format!("{},{}", column, row);
-        }
-    }
+    let mut cart_system = simulate(input);
+    let cart = cart_system.run().unwrap();
+    format!("{},{}", cart.column, cart.row)
 }
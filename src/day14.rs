@@ -1,60 +1,164 @@
-#[aoc(day14, part1)]
-pub fn solve_day14_part1(input: &str) -> String {
-    let num_rounds = input.parse::<usize>().unwrap();
-    let mut leaderboard = vec![3, 7];
-    let (mut first_index, mut second_index) = (0, 1);
-    let mut total;
-    loop {
-        total = leaderboard[first_index] + leaderboard[second_index];
-        if total >= 10 {
-            leaderboard.push(total / 10);
-            leaderboard.push(total % 10);
-        } else {
-            leaderboard.push(total);
-        }
-        if leaderboard.len() > num_rounds + 10 {
-            return leaderboard[num_rounds..num_rounds + 10]
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<_>>()
-                .join("");
-        }
-        first_index += leaderboard[first_index] + 1;
-        first_index %= leaderboard.len();
-        second_index += leaderboard[second_index] + 1;
-        second_index %= leaderboard.len();
+use std::collections::VecDeque;
+
+// yields every recipe score in order, generating more as the elves need them
+pub struct Scoreboard {
+    scores: Vec<u8>,
+    elves: Vec<usize>,
+    base: usize,
+    next: usize,
+}
+
+impl Scoreboard {
+    pub fn new() -> Self {
+        Scoreboard::with_elves(vec![3, 7], 2, 10)
+    }
+
+    // elf i starts on recipe i, new scores are the digits of the elves' total in the given base
+    pub fn with_elves(initial: Vec<u8>, num_elves: usize, base: usize) -> Self {
+        assert!(
+            num_elves > 0 && num_elves <= initial.len(),
+            "Weird number of elves"
+        );
+        assert!((2..=256).contains(&base), "Weird base: {}", base);
+        assert!(
+            initial.iter().all(|score| (*score as usize) < base),
+            "Initial score isn't a digit"
+        );
+        Scoreboard {
+            scores: initial,
+            elves: (0..num_elves).collect(),
+            base,
+            next: 0,
+        }
+    }
+
+    fn step(&mut self) {
+        let mut total: usize = self.elves.iter().map(|i| self.scores[*i] as usize).sum();
+        let mut digits = vec![(total % self.base) as u8];
+        total /= self.base;
+        while total > 0 {
+            digits.push((total % self.base) as u8);
+            total /= self.base;
+        }
+        self.scores.extend(digits.into_iter().rev());
+        for elf in &mut self.elves {
+            *elf = (*elf + self.scores[*elf] as usize + 1) % self.scores.len();
+        }
     }
 }
 
-#[aoc(day14, part2)]
-pub fn solve_day14_part2(input: &str) -> usize {
-    let puzzle: Vec<_> = input
+impl Default for Scoreboard {
+    fn default() -> Self {
+        Scoreboard::new()
+    }
+}
+
+impl Iterator for Scoreboard {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.next >= self.scores.len() {
+            self.step();
+        }
+        self.next += 1;
+        Some(self.scores[self.next - 1])
+    }
+}
+
+// Aho-Corasick automaton over the digits of a base, fed one digit at a time
+pub struct Matcher {
+    transitions: Vec<Vec<usize>>,
+    outputs: Vec<Vec<usize>>,
+    state: usize,
+}
+
+impl Matcher {
+    pub fn new(patterns: &[Vec<u8>], base: usize) -> Self {
+        let mut transitions = vec![vec![0; base]];
+        let mut outputs = vec![Vec::new()];
+        for (index, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for digit in pattern {
+                if transitions[state][*digit as usize] == 0 {
+                    transitions.push(vec![0; base]);
+                    outputs.push(Vec::new());
+                    transitions[state][*digit as usize] = transitions.len() - 1;
+                }
+                state = transitions[state][*digit as usize];
+            }
+            outputs[state].push(index);
+        }
+        // breadth first so every failure state is finished before it's used
+        let mut failures = vec![0; transitions.len()];
+        let mut queue: VecDeque<_> = transitions[0].iter().cloned().filter(|s| *s != 0).collect();
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[failures[state]].clone();
+            outputs[state].extend(inherited);
+            let (row, fallbacks) = (
+                transitions[state].clone(),
+                transitions[failures[state]].clone(),
+            );
+            for (digit, next) in row.iter().enumerate() {
+                if *next == 0 {
+                    transitions[state][digit] = fallbacks[digit];
+                } else {
+                    failures[*next] = fallbacks[digit];
+                    queue.push_back(*next);
+                }
+            }
+        }
+        Matcher {
+            transitions,
+            outputs,
+            state: 0,
+        }
+    }
+
+    // indices of the patterns that end with this digit
+    pub fn feed(&mut self, digit: u8) -> &[usize] {
+        self.state = self.transitions[self.state][digit as usize];
+        &self.outputs[self.state]
+    }
+}
+
+fn get_digits(input: &str) -> Vec<u8> {
+    input
+        .trim()
         .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .collect();
-    let mut leaderboard = vec![3, 7];
-    let (mut first_index, mut second_index) = (0, 1);
-    let mut total;
-    loop {
-        total = leaderboard[first_index] + leaderboard[second_index];
-        if total >= 10 {
-            leaderboard.push(total / 10);
-            leaderboard.push(total % 10);
-        } else {
-            leaderboard.push(total);
-        }
-        if leaderboard.len() > puzzle.len() {
-            if leaderboard[leaderboard.len() - puzzle.len()..] == puzzle[..] {
-                return leaderboard.len() - puzzle.len();
-            } else if leaderboard[leaderboard.len() - puzzle.len() - 1..leaderboard.len() - 1]
-                == puzzle[..]
-            {
-                return leaderboard.len() - puzzle.len() - 1;
+        .map(|c| c.to_digit(10).unwrap() as u8)
+        .collect()
+}
+
+// position of the first appearance of each pattern, found in a single pass
+pub fn get_first_positions(scoreboard: Scoreboard, patterns: &[Vec<u8>]) -> Vec<usize> {
+    let mut matcher = Matcher::new(patterns, scoreboard.base);
+    let mut positions = vec![None; patterns.len()];
+    let mut num_found = 0;
+    for (i, digit) in scoreboard.enumerate() {
+        for index in matcher.feed(digit) {
+            if positions[*index].is_none() {
+                positions[*index] = Some(i + 1 - patterns[*index].len());
+                num_found += 1;
             }
         }
-        first_index += leaderboard[first_index] + 1;
-        first_index %= leaderboard.len();
-        second_index += leaderboard[second_index] + 1;
-        second_index %= leaderboard.len();
+        if num_found == patterns.len() {
+            break;
+        }
     }
+    positions.into_iter().map(|p| p.unwrap()).collect()
+}
+
+#[aoc(day14, part1)]
+pub fn solve_day14_part1(input: &str) -> String {
+    let num_rounds = input.trim().parse::<usize>().unwrap();
+    Scoreboard::new()
+        .skip(num_rounds)
+        .take(10)
+        .map(|x| x.to_string())
+        .collect()
+}
+
+#[aoc(day14, part2)]
+pub fn solve_day14_part2(input: &str) -> usize {
+    get_first_positions(Scoreboard::new(), &[get_digits(input)])[0]
 }
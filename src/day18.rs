@@ -1,6 +1,7 @@
-use hashbrown::HashSet;
-
-pub const NUM_ACRES: usize = 50;
+use cycle::find_cycle;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Acre {
@@ -10,111 +11,178 @@ pub enum Acre {
 }
 
 impl Acre {
-    pub fn change(&self, adjacent: &[Acre]) -> Acre {
+    pub fn change(self, num_trees: usize, num_lumberyards: usize) -> Acre {
         match self {
-            Acre::Open => {
-                if adjacent.into_iter().filter(|a| **a == Acre::Trees).count() >= 3 {
-                    Acre::Trees
-                } else {
-                    Acre::Open
-                }
-            }
-            Acre::Trees => {
-                if adjacent
-                    .into_iter()
-                    .filter(|a| **a == Acre::Lumberyard)
-                    .count()
-                    >= 3
-                {
-                    Acre::Lumberyard
-                } else {
-                    Acre::Trees
-                }
-            }
-            Acre::Lumberyard => {
-                if adjacent.iter().filter(|a| **a == Acre::Lumberyard).count() >= 1
-                    && adjacent.iter().filter(|a| **a == Acre::Trees).count() >= 1
-                {
-                    Acre::Lumberyard
-                } else {
-                    Acre::Open
-                }
-            }
+            Acre::Open if num_trees >= 3 => Acre::Trees,
+            Acre::Trees if num_lumberyards >= 3 => Acre::Lumberyard,
+            Acre::Lumberyard if num_trees == 0 || num_lumberyards == 0 => Acre::Open,
+            acre => acre,
         }
     }
 }
 
-pub struct LumberCollectionArea {
-    area: [[Acre; NUM_ACRES]; NUM_ACRES],
+#[derive(Debug, PartialEq, Eq)]
+pub enum AreaError {
+    Empty,
+    BlankLine {
+        row: usize,
+    },
+    UnknownAcre {
+        row: usize,
+        column: usize,
+        acre: char,
+    },
+    WrongWidth {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
 }
 
-impl LumberCollectionArea {
-    pub fn read(input: &str) -> Self {
-        let mut area = [[Acre::Open; NUM_ACRES]; NUM_ACRES];
-        for (i, line) in input.split('\n').enumerate() {
-            for (j, c) in line.chars().enumerate() {
-                match c {
-                    '.' => area[i][j] = Acre::Open,
-                    '|' => area[i][j] = Acre::Trees,
-                    '#' => area[i][j] = Acre::Lumberyard,
-                    _ => panic!("Unknown acre"),
-                }
+impl fmt::Display for AreaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AreaError::Empty => write!(f, "No acres"),
+            AreaError::BlankLine { row } => write!(f, "Row {} is blank", row),
+            AreaError::UnknownAcre { row, column, acre } => {
+                write!(f, "Unknown acre {:?} at {},{}", acre, column, row)
             }
+            AreaError::WrongWidth {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "Row {} is {} acres wide instead of {}",
+                row, width, expected
+            ),
         }
-        LumberCollectionArea { area }
     }
+}
 
-    pub fn get_adjacent(&self, x: usize, y: usize) -> Vec<Acre> {
-        let mut adjacent_acres = Vec::new();
-        if x > 0 {
-            adjacent_acres.push(self.area[x - 1][y]);
-            if y > 0 {
-                adjacent_acres.push(self.area[x - 1][y - 1]);
-            }
-            if y < NUM_ACRES - 1 {
-                adjacent_acres.push(self.area[x - 1][y + 1]);
-            }
+impl Error for AreaError {}
+
+// acres are stored row by row with a border of open acres, so every cell has eight neighbours,
+// the next minute is written into the second buffer and then the two are swapped
+#[derive(Clone)]
+pub struct LumberCollectionArea {
+    width: usize,
+    height: usize,
+    area: Vec<Acre>,
+    next_area: Vec<Acre>,
+}
+
+impl PartialEq for LumberCollectionArea {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.area == other.area
+    }
+}
+
+impl Eq for LumberCollectionArea {}
+
+impl Hash for LumberCollectionArea {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.area.hash(state);
+    }
+}
+
+impl LumberCollectionArea {
+    pub fn read(input: &str) -> Result<Self, AreaError> {
+        // only blank lines at the end are allowed
+        let mut lines: Vec<_> = input.lines().collect();
+        while lines.last() == Some(&"") {
+            lines.pop();
         }
-        if x < NUM_ACRES - 1 {
-            adjacent_acres.push(self.area[x + 1][y]);
-            if y > 0 {
-                adjacent_acres.push(self.area[x + 1][y - 1]);
+        let width = lines.first().ok_or(AreaError::Empty)?.chars().count();
+        let height = lines.len();
+        let mut area = vec![Acre::Open; (width + 2) * (height + 2)];
+        for (i, line) in lines.iter().enumerate() {
+            if line.is_empty() {
+                return Err(AreaError::BlankLine { row: i });
             }
-            if y < NUM_ACRES - 1 {
-                adjacent_acres.push(self.area[x + 1][y + 1]);
+            let row_width = line.chars().count();
+            if row_width != width {
+                return Err(AreaError::WrongWidth {
+                    row: i,
+                    width: row_width,
+                    expected: width,
+                });
+            }
+            for (j, c) in line.chars().enumerate() {
+                area[(i + 1) * (width + 2) + j + 1] = match c {
+                    '.' => Acre::Open,
+                    '|' => Acre::Trees,
+                    '#' => Acre::Lumberyard,
+                    _ => {
+                        return Err(AreaError::UnknownAcre {
+                            row: i,
+                            column: j,
+                            acre: c,
+                        })
+                    }
+                };
             }
         }
-        if y > 0 {
-            adjacent_acres.push(self.area[x][y - 1]);
-        }
-        if y < NUM_ACRES - 1 {
-            adjacent_acres.push(self.area[x][y + 1]);
-        }
-
-        adjacent_acres
+        Ok(LumberCollectionArea {
+            width,
+            height,
+            next_area: area.clone(),
+            area,
+        })
     }
 
     pub fn change(&mut self) -> bool {
-        let mut new_area = [[Acre::Open; NUM_ACRES]; NUM_ACRES];
+        let stride = self.width + 2;
+        let offsets = [
+            0,
+            1,
+            2,
+            stride,
+            stride + 2,
+            2 * stride,
+            2 * stride + 1,
+            2 * stride + 2,
+        ];
         let mut changed = false;
-        for i in 0..NUM_ACRES {
-            for j in 0..NUM_ACRES {
-                new_area[i][j] = self.area[i][j].change(&self.get_adjacent(i, j));
-                if new_area[i][j] != self.area[i][j] {
-                    changed = true;
+        for i in 0..self.height {
+            for j in 0..self.width {
+                // the top left neighbour of the acre is at i, j once the border is counted
+                let corner = i * stride + j;
+                let (mut num_trees, mut num_lumberyards) = (0, 0);
+                for offset in &offsets {
+                    match self.area[corner + offset] {
+                        Acre::Trees => num_trees += 1,
+                        Acre::Lumberyard => num_lumberyards += 1,
+                        Acre::Open => (),
+                    }
                 }
+                let acre = self.area[corner + stride + 1];
+                let next = acre.change(num_trees, num_lumberyards);
+                changed |= next != acre;
+                self.next_area[corner + stride + 1] = next;
             }
         }
-        self.area = new_area;
+        ::std::mem::swap(&mut self.area, &mut self.next_area);
         changed
     }
 
+    // skips ahead through the cycle the area ends up in instead of simulating every minute
+    pub fn run(&mut self, num_minutes: usize) {
+        let cycle = find_cycle(self.clone(), |area| {
+            let mut next = area.clone();
+            next.change();
+            next
+        });
+        *self = cycle.get(num_minutes).clone();
+    }
+
     pub fn display(&self) {
-        for i in 0..NUM_ACRES {
-            for j in 0..NUM_ACRES {
+        for row in self.area.chunks(self.width + 2).skip(1).take(self.height) {
+            for acre in &row[1..=self.width] {
                 print!(
                     "{}",
-                    match self.area[i][j] {
+                    match acre {
                         Acre::Open => '.',
                         Acre::Trees => '|',
                         Acre::Lumberyard => '#',
@@ -128,13 +196,11 @@ impl LumberCollectionArea {
 
     pub fn get_counts(&self) -> (usize, usize) {
         let (mut num_trees, mut num_lumberyards) = (0, 0);
-        for i in 0..NUM_ACRES {
-            for j in 0..NUM_ACRES {
-                match self.area[i][j] {
-                    Acre::Trees => num_trees += 1,
-                    Acre::Lumberyard => num_lumberyards += 1,
-                    _ => (),
-                }
+        for acre in &self.area {
+            match acre {
+                Acre::Trees => num_trees += 1,
+                Acre::Lumberyard => num_lumberyards += 1,
+                _ => (),
             }
         }
         (num_trees, num_lumberyards)
@@ -143,7 +209,7 @@ impl LumberCollectionArea {
 
 #[aoc(day18, part1)]
 pub fn solve_day18_part1(input: &str) -> usize {
-    let mut area = LumberCollectionArea::read(input);
+    let mut area = LumberCollectionArea::read(input).unwrap();
     for _ in 0..10 {
         area.change();
     }
@@ -153,44 +219,8 @@ pub fn solve_day18_part1(input: &str) -> usize {
 
 #[aoc(day18, part2)]
 pub fn solve_day18_part2(input: &str) -> usize {
-    let mut area = LumberCollectionArea::read(input);
-    let mut counts = HashSet::new();
-    let mut c_t_l;
-    let mut num_done = 0;
-    let mut found_x = 0;
-    loop {
-        c_t_l = area.get_counts();
-        if found_x == 0 && counts.contains(&c_t_l) {
-            found_x += 1;
-        }
-        if found_x > 0 {
-            if !counts.contains(&c_t_l) {
-                found_x = 0;
-            } else {
-                found_x += 1;
-                if found_x > 4 {
-                    break;
-                }
-            }
-        }
-        counts.insert(c_t_l);
-        area.change();
-        num_done += 1;
-    }
-    let mut period = 1;
-    let mut new_c_t_l;
-    loop {
-        area.change();
-        new_c_t_l = area.get_counts();
-        if new_c_t_l == c_t_l {
-            break;
-        }
-        period += 1;
-    }
-    let num_steps = (1000000000usize - num_done - period) % period;
-    for _ in 0..num_steps {
-        area.change();
-    }
+    let mut area = LumberCollectionArea::read(input).unwrap();
+    area.run(1_000_000_000);
     let (num_trees, num_lumberyards) = area.get_counts();
     num_trees * num_lumberyards
 }
@@ -1,52 +1,85 @@
-#[aoc(day2, part1)]
-pub fn solve_day2_part1(input: &str) -> usize {
-    let box_ids: Vec<_> = input.split('\n').collect();
-    let alphabet: Vec<_> = (b'a'..b'z').map(|x| x as char).collect();
-    let letter_counts: Vec<_> = box_ids
-        .into_iter()
-        .map(|w| {
-            (
-                is_letter_count(w, &alphabet, 2),
-                is_letter_count(w, &alphabet, 3),
-            )
-        })
-        .collect();
-    letter_counts.iter().filter(|(x, _)| *x).count()
-        * letter_counts.into_iter().filter(|(_, x)| *x).count()
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+fn get_byte_counts(word: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for byte in word {
+        counts[*byte as usize] += 1;
+    }
+    counts
 }
 
-#[aoc(day2, part2)]
-pub fn solve_day2_part2(input: &str) -> String {
-    let box_ids: Vec<Vec<_>> = input.split('\n').map(|s| s.chars().collect()).collect();
-    for i in 0..(box_ids.len() - 1) {
-        for j in (i + 1)..box_ids.len() {
-            if get_hamming(&box_ids[i], &box_ids[j]) == 1 {
-                return box_ids[i]
-                    .iter()
-                    .zip(box_ids[j].iter())
-                    .filter(|(c1, c2)| c1 == c2)
-                    .map(|(c, _)| *c)
-                    .collect();
+pub fn get_checksum(box_ids: &[&[u8]]) -> usize {
+    let (mut num_twos, mut num_threes) = (0, 0);
+    for box_id in box_ids {
+        let counts = get_byte_counts(box_id);
+        if counts.contains(&2) {
+            num_twos += 1;
+        }
+        if counts.contains(&3) {
+            num_threes += 1;
+        }
+    }
+    num_twos * num_threes
+}
+
+// Pairs of IDs that differ in exactly `distance` positions. Each ID is filed under every way
+// of masking that many positions, and two IDs only meet under the mask of the positions they
+// differ in, where none of the masked bytes match.
+pub fn find_pairs(box_ids: &[&[u8]], distance: usize) -> Vec<(usize, usize)> {
+    let mut buckets: HashMap<(Vec<usize>, Vec<u8>), Vec<usize>> = HashMap::new();
+    for (i, box_id) in box_ids.iter().enumerate() {
+        for positions in (0..box_id.len()).combinations(distance) {
+            let mut kept = Vec::with_capacity(box_id.len() - distance);
+            let mut masked = positions.iter().peekable();
+            for (j, byte) in box_id.iter().enumerate() {
+                if masked.peek() == Some(&&j) {
+                    masked.next();
+                } else {
+                    kept.push(*byte);
+                }
             }
+            buckets
+                .entry((positions, kept))
+                .or_insert_with(Vec::new)
+                .push(i);
         }
     }
-    String::new()
+    let mut pairs = Vec::new();
+    for ((positions, _), indices) in &buckets {
+        for (a, b) in indices.iter().tuple_combinations() {
+            if positions.iter().all(|p| box_ids[*a][*p] != box_ids[*b][*p]) {
+                pairs.push((*a, *b));
+            }
+        }
+    }
+    pairs.sort();
+    pairs
 }
 
-fn get_hamming(word_1: &[char], word_2: &[char]) -> usize {
-    word_1
-        .iter()
-        .zip(word_2.iter())
-        .filter(|(c1, c2)| c1 != c2)
-        .count()
+fn get_box_ids(input: &str) -> Vec<&[u8]> {
+    input
+        .split('\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| line.as_bytes())
+        .collect()
 }
 
-fn is_letter_count(word: &str, alphabet: &[char], number: usize) -> bool {
-    let word_chars: Vec<_> = word.chars().collect();
-    for letter in alphabet {
-        if word_chars.iter().filter(|c| *c == letter).count() == number {
-            return true;
-        }
+#[aoc(day2, part1)]
+pub fn solve_day2_part1(input: &str) -> usize {
+    get_checksum(&get_box_ids(input))
+}
+
+#[aoc(day2, part2)]
+pub fn solve_day2_part2(input: &str) -> String {
+    let box_ids = get_box_ids(input);
+    match find_pairs(&box_ids, 1).first() {
+        Some((i, j)) => box_ids[*i]
+            .iter()
+            .zip(box_ids[*j].iter())
+            .filter(|(c1, c2)| c1 == c2)
+            .map(|(c, _)| *c as char)
+            .collect(),
+        None => String::new(),
     }
-    false
 }
@@ -1,56 +1,115 @@
 use hashbrown::{HashSet, HashMap};
 use itertools::Itertools;
 use regex::Regex;
+use std::error::Error;
+use std::fmt;
+
+#[derive(EnumString, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttackType {
+    #[strum(serialize = "bludgeoning")]
+    Bludgeoning,
+    #[strum(serialize = "cold")]
+    Cold,
+    #[strum(serialize = "fire")]
+    Fire,
+    #[strum(serialize = "radiation")]
+    Radiation,
+    #[strum(serialize = "slashing")]
+    Slashing,
+}
+
+impl AttackType {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GroupError {
+    Malformed(String),
+    UnknownModifier(String),
+    UnknownAttackType(String),
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupError::Malformed(line) => write!(f, "Weird group: {}", line),
+            GroupError::UnknownModifier(modifier) => write!(f, "Unknown modifier: {}", modifier),
+            GroupError::UnknownAttackType(attack_type) => {
+                write!(f, "Unknown attack type: {}", attack_type)
+            }
+        }
+    }
+}
+
+impl Error for GroupError {}
+
+fn parse_attack_type(attack_type: &str) -> Result<AttackType, GroupError> {
+    attack_type
+        .parse()
+        .map_err(|_| GroupError::UnknownAttackType(attack_type.to_owned()))
+}
 
 #[derive(Clone, Debug)]
 pub struct Group {
     units: usize,
     hit_points: usize,
-    weak_to: Vec<String>,
-    immune_to: Vec<String>,
+    weak_to: u8,
+    immune_to: u8,
     attack_damage: usize,
-    attack_type: String,
+    attack_type: AttackType,
     initiative: usize,
 }
 
 impl Group {
-    fn parse(lines: &str) -> Vec<Self> {
+    fn parse(lines: &str) -> Result<Vec<Self>, GroupError> {
         let group_re =
-            Regex::new(r"([0-9]+) units each with ([0-9]+) hit points (?:\(([a-z,; ]+)\) )??with an attack that does ([0-9]+) ([a-z]+) damage at initiative ([0-9]+)").unwrap();
-        let weak_re = Regex::new(r".*weak to ([a-z, ]+)").unwrap();
-        let immune_re = Regex::new(r".*immune to ([a-z, ]+)").unwrap();
+            Regex::new(r"^([0-9]+) units each with ([0-9]+) hit points (?:\(([a-z,; ]+)\) )??with an attack that does ([0-9]+) ([a-z]+) damage at initiative ([0-9]+)$").unwrap();
         lines
             .split('\n')
             .skip(1)
+            .filter(|line| !line.is_empty())
             .map(|line| {
-                let group = group_re.captures(line).unwrap();
-                let (mut weak_to, mut immune_to) = (Vec::new(), Vec::new());
+                let group = group_re
+                    .captures(line)
+                    .ok_or_else(|| GroupError::Malformed(line.to_owned()))?;
+                let (mut weak_to, mut immune_to) = (0, 0);
                 if let Some(part) = group.get(3) {
-                    if let Some(weak) = weak_re.captures(&part.as_str()) {
-                        weak_to.extend(weak[1].split(", ").map(|s| s.to_owned()));
-                    }
-                    if let Some(immune) = immune_re.captures(&part.as_str()) {
-                        immune_to.extend(immune[1].split(", ").map(|s| s.to_owned()));
+                    // clauses can come in either order
+                    for clause in part.as_str().split("; ") {
+                        let (modifier, attack_types) = clause
+                            .splitn(2, " to ")
+                            .collect_tuple()
+                            .ok_or_else(|| GroupError::UnknownModifier(clause.to_owned()))?;
+                        let types = match modifier {
+                            "weak" => &mut weak_to,
+                            "immune" => &mut immune_to,
+                            _ => return Err(GroupError::UnknownModifier(modifier.to_owned())),
+                        };
+                        for attack_type in attack_types.split(", ") {
+                            *types |= parse_attack_type(attack_type)?.bit();
+                        }
                     }
                 }
                 let (units, attack_damage) = (group[1].parse().unwrap(), group[4].parse().unwrap());
-                Group {
+                Ok(Group {
                     units,
                     hit_points: group[2].parse().unwrap(),
                     weak_to,
                     immune_to,
                     attack_damage,
-                    attack_type: group[5].to_string(),
+                    attack_type: parse_attack_type(&group[5])?,
                     initiative: group[6].parse().unwrap(),
-                }
+                })
             })
             .collect()
     }
 
     fn calculate_damage(&self, enemy: &Group) -> usize {
-        let multiplier = if enemy.weak_to.contains(&self.attack_type) {
+        let multiplier = if enemy.weak_to & self.attack_type.bit() != 0 {
             2
-        } else if enemy.immune_to.contains(&self.attack_type) {
+        } else if enemy.immune_to & self.attack_type.bit() != 0 {
             0
         } else {
             1
@@ -83,19 +142,13 @@ impl Group {
         }
     }
 
-    fn take_damage(&mut self, damage: usize) -> bool {
-        let mut damage = damage;
-        while damage >= self.hit_points {
-            self.units -= 1;
-            if self.units == 0 {
-                return true;
-            }
-            damage -= self.hit_points;
-        }
-        false
+    fn take_damage(&mut self, damage: usize) -> usize {
+        let killed = self.units.min(damage / self.hit_points);
+        self.units -= killed;
+        killed
     }
 
-    pub fn attack(&mut self, attacker: &Group) -> bool {
+    pub fn attack(&mut self, attacker: &Group) -> usize {
         let damage = attacker.calculate_damage(&self);
         self.take_damage(damage)
     }
@@ -109,6 +162,77 @@ impl Group {
 pub struct Battle {
     immune_system: HashMap<usize, Group>,
     infection: HashMap<usize, Group>,
+    num_immune_system_groups: usize,
+}
+
+pub struct Selection {
+    pub attacker: usize,
+    pub defender: usize,
+    pub damage: usize,
+    pub chosen: bool,
+}
+
+pub struct Attack {
+    pub attacker: usize,
+    pub defender: usize,
+    pub units_killed: usize,
+}
+
+// groups are numbered from 0 with the immune system first, like in the battle
+pub struct Round {
+    pub number: usize,
+    pub summary: String,
+    pub selections: Vec<Selection>,
+    pub attacks: Vec<Attack>,
+    num_immune_system_groups: usize,
+}
+
+impl Round {
+    fn get_name(&self, group: usize) -> (&'static str, usize) {
+        if group < self.num_immune_system_groups {
+            ("Immune System", group + 1)
+        } else {
+            ("Infection", group - self.num_immune_system_groups + 1)
+        }
+    }
+}
+
+// same layout as the worked example in the puzzle
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.summary)?;
+        let immune_system_last = |s: &&Selection| {
+            (
+                s.attacker < self.num_immune_system_groups,
+                s.attacker,
+                s.defender,
+            )
+        };
+        for selection in self.selections.iter().sorted_by_key(immune_system_last) {
+            let (army, number) = self.get_name(selection.attacker);
+            writeln!(
+                f,
+                "{} group {} would deal defending group {} {} damage",
+                army,
+                number,
+                self.get_name(selection.defender).1,
+                selection.damage
+            )?;
+        }
+        writeln!(f)?;
+        for attack in &self.attacks {
+            let (army, number) = self.get_name(attack.attacker);
+            writeln!(
+                f,
+                "{} group {} attacks defending group {}, killing {} units",
+                army,
+                number,
+                self.get_name(attack.defender).1,
+                attack.units_killed
+            )?;
+        }
+        Ok(())
+    }
 }
 
 impl Battle {
@@ -119,11 +243,17 @@ impl Battle {
                 .into_iter()
                 .enumerate()
                 .collect(),
-            infection: infection_groups.into_iter().enumerate().map(|(i, g)| (i + num_immune_system_groups, g)).collect()
+            infection: infection_groups.into_iter().enumerate().map(|(i, g)| (i + num_immune_system_groups, g)).collect(),
+            num_immune_system_groups,
         }
     }
 
     pub fn target_selection(&self) -> HashMap<usize, usize> {
+        self.select_targets().0
+    }
+
+    // also returns every target each group considered, with the damage it would deal
+    fn select_targets(&self) -> (HashMap<usize, usize>, Vec<Selection>) {
         let order = self
             .immune_system
             .iter()
@@ -132,57 +262,90 @@ impl Battle {
                 (a.1.effective_power(), a.1.initiative).cmp(&(b.1.effective_power(), b.1.initiative))
             });
         let mut chosen = HashSet::new();
-        order
+        let mut selections = Vec::new();
+        let targets = order
             .into_iter()
             .filter_map(|(i, group)| {
-                let enemies = if self.immune_system.contains_key(i) {
+                let enemies: HashMap<_, _> = if self.immune_system.contains_key(i) {
                     self.infection.iter().filter(|(i, _)| !chosen.contains(*i)).map(|(i, g)| (*i, g)).collect()
                 } else {
                     self.immune_system.iter().filter(|(i, _)| !chosen.contains(*i)).map(|(i, g)| (*i, g)).collect()
                 };
-                if let Some(enemy) = group.choose_target(&enemies) {
+                let target = group.choose_target(&enemies);
+                for (enemy, enemy_group) in &enemies {
+                    let damage = group.calculate_damage(enemy_group);
+                    if damage > 0 {
+                        selections.push(Selection {
+                            attacker: *i,
+                            defender: *enemy,
+                            damage,
+                            chosen: target == Some(*enemy),
+                        });
+                    }
+                }
+                if let Some(enemy) = target {
                     chosen.insert(enemy);
                     Some((*i, enemy))
                 } else {
                     None
                 }
             })
-            .collect()
+            .collect();
+        (targets, selections)
     }
 
-    pub fn attack(&mut self, targets: &HashMap<usize, usize>) {
+    pub fn attack(&mut self, targets: &HashMap<usize, usize>) -> Vec<Attack> {
         let order = targets.keys()
             .sorted_by(|b, a| {
                 let a_group = if self.immune_system.contains_key(a) {self.immune_system[a].initiative} else {self.infection[a].initiative};
                 let b_group = if self.immune_system.contains_key(b) {self.immune_system[b].initiative} else {self.infection[b].initiative};
                 a_group.cmp(&b_group)
             });
+        let mut attacks = Vec::new();
         for unit in order {
-            if !self.immune_system.contains_key(unit) && !self.infection.contains_key(unit){
-                continue;
-            }
             let enemy_index = targets[unit];
-            if !self.immune_system.contains_key(&enemy_index) && !self.infection.contains_key(&enemy_index) {
+            let (attackers, defenders) = if self.immune_system.contains_key(unit) {
+                (&self.immune_system, &mut self.infection)
+            } else if self.infection.contains_key(unit) {
+                (&self.infection, &mut self.immune_system)
+            } else {
                 continue;
+            };
+            let units_killed = match defenders.get_mut(&enemy_index) {
+                Some(enemy_group) => enemy_group.attack(&attackers[unit]),
+                None => continue,
+            };
+            if defenders[&enemy_index].units == 0 {
+                defenders.remove(&enemy_index);
             }
-            let against_infection = if self.immune_system.contains_key(unit) { true } else { false };
-            let mut killed = false;
-            if against_infection {
-                if let Some(enemy_group) = self.infection.get_mut(&enemy_index) {
-                    killed = enemy_group.attack(&self.immune_system[unit])
-                }
-                if killed {
-                    self.infection.remove(&enemy_index);
-                }
-            } else {
-                if let Some(enemy_group) = self.immune_system.get_mut(&enemy_index) {
-                    killed = enemy_group.attack(&self.infection[unit]);
-                }
-                if killed {
-                    self.immune_system.remove(&enemy_index);
-                }
+            attacks.push(Attack {
+                attacker: *unit,
+                defender: enemy_index,
+                units_killed,
+            });
+        }
+        attacks
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for (name, army, offset) in &[
+            ("Immune System", &self.immune_system, 0),
+            ("Infection", &self.infection, self.num_immune_system_groups),
+        ] {
+            summary.push_str(&format!("{}:\n", name));
+            if army.is_empty() {
+                summary.push_str("No groups remain.\n");
+            }
+            for (i, group) in army.iter().sorted_by_key(|(i, _)| **i) {
+                summary.push_str(&format!(
+                    "Group {} contains {} units\n",
+                    i - offset + 1,
+                    group.units
+                ));
             }
         }
+        summary
     }
 
     pub fn boost_immune_system(&mut self, boost_value: usize) {
@@ -191,48 +354,138 @@ impl Battle {
         }
     }
 
-    pub fn battle(&mut self) {
-        let mut num_fights = 0;
+    fn count_units(&self) -> (usize, usize) {
+        (
+            self.immune_system.values().map(|g| g.units).sum(),
+            self.infection.values().map(|g| g.units).sum(),
+        )
+    }
+
+    pub fn battle(&mut self) -> Outcome {
+        self.fight(None)
+    }
+
+    // fights until one army is gone, or until a round passes in which no units die,
+    // every round is added to the log if there is one
+    pub fn fight(&mut self, mut log: Option<&mut Vec<Round>>) -> Outcome {
+        let (mut units, mut number) = (self.count_units(), 0);
         loop {
-            if num_fights > 3000 || self.immune_system.is_empty() || self.infection.is_empty() {
-                break;
+            number += 1;
+            if self.infection.is_empty() {
+                return Outcome::ImmuneWin(units.0);
+            }
+            if self.immune_system.is_empty() {
+                return Outcome::InfectionWin(units.1);
+            }
+            let (targets, selections) = self.select_targets();
+            let round = log.as_ref().map(|_| Round {
+                number,
+                summary: self.summary(),
+                selections,
+                attacks: Vec::new(),
+                num_immune_system_groups: self.num_immune_system_groups,
+            });
+            let attacks = self.attack(&targets);
+            if let (Some(log), Some(mut round)) = (log.as_mut(), round) {
+                round.attacks = attacks;
+                log.push(round);
+            }
+            let remaining = self.count_units();
+            if remaining == units {
+                return Outcome::Stalemate {
+                    immune_system: units.0,
+                    infection: units.1,
+                };
+            }
+            units = remaining;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    ImmuneWin(usize),
+    InfectionWin(usize),
+    Stalemate { immune_system: usize, infection: usize },
+}
+
+impl Outcome {
+    pub fn remaining_units(&self) -> usize {
+        match self {
+            Outcome::ImmuneWin(units) | Outcome::InfectionWin(units) => *units,
+            Outcome::Stalemate {
+                immune_system,
+                infection,
+            } => immune_system + infection,
+        }
+    }
+}
+
+fn battle_with_boost(battle: &Battle, boost: usize) -> Outcome {
+    let mut battle = battle.clone();
+    battle.boost_immune_system(boost);
+    battle.battle()
+}
+
+// Assumes a bigger boost never helps the infection, so the boosts the infection wins at come
+// first and can be binary searched. Stalemates can still be mixed in with immune system wins
+// after that, so the smallest winning boost is found by walking up from there.
+// Once the boost alone is as big as the largest infection group's total hit points, every hit
+// that does any damage kills its target and more boost can't change anything, so the search
+// gives up there.
+pub fn find_smallest_boost(battle: &Battle) -> Option<(usize, usize)> {
+    let max_boost = battle
+        .infection
+        .values()
+        .map(|group| group.units * group.hit_points)
+        .max()
+        .unwrap_or(0);
+    let infection_wins =
+        |boost| matches!(battle_with_boost(battle, boost), Outcome::InfectionWin(_));
+    let mut high = 0;
+    if infection_wins(0) {
+        let mut low = 0;
+        high = 1;
+        while infection_wins(high) {
+            if high >= max_boost {
+                return None;
             }
-            let targets = self.target_selection();
-            if targets.is_empty() {
-                break;
+            low = high;
+            high = (high * 2).min(max_boost);
+        }
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if infection_wins(middle) {
+                low = middle;
+            } else {
+                high = middle;
             }
-            self.attack(&targets);
-            num_fights += 1;
         }
     }
+    (high..=max_boost.max(high))
+        .filter_map(|boost| match battle_with_boost(battle, boost) {
+            Outcome::ImmuneWin(units) => Some((boost, units)),
+            _ => None,
+        })
+        .next()
 }
 
 #[aoc_generator(day24)]
 pub fn generate_day24(input: &str) -> Box<Battle> {
     let (immune_system, infection) = input.split("\n\n").collect_tuple().unwrap();
-    let immune_system_groups = Group::parse(immune_system);
-    let infection_groups = Group::parse(infection);
+    let immune_system_groups = Group::parse(immune_system).unwrap();
+    let infection_groups = Group::parse(infection).unwrap();
     Box::new(Battle::new(immune_system_groups, infection_groups))
 }
 
 #[aoc(day24, part1)]
 pub fn solve_day24_part1(input: &Battle) -> usize {
-    let mut battle = input.clone();
-    battle.battle();
-    let immune_system_sum = battle.immune_system.values().map(|g| g.units).sum::<usize>();
-    let infection_sum = battle.infection.values().map(|g| g.units).sum();
-    immune_system_sum.max(infection_sum)
+    input.clone().battle().remaining_units()
 }
 
 #[aoc(day24, part2)]
 pub fn solve_day24_part2(input: &Battle) -> usize {
-    for boost in 30.. {
-        let mut battle = input.clone();
-        battle.boost_immune_system(boost);
-        battle.battle();
-        if battle.infection.is_empty() {
-            return battle.immune_system.values().map(|g| g.units).sum()
-        }
-    }
-    0
+    find_smallest_boost(input)
+        .expect("No boost lets the immune system win")
+        .1
 }
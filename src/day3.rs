@@ -1,5 +1,12 @@
+use hashbrown::HashMap;
+use image::{heat_colour, Image};
 use itertools::Itertools;
 use ndarray::{Array2, ArrayView};
+use petgraph::algo::condensation;
+use petgraph::dot::Dot;
+use petgraph::{Graph, Undirected};
+
+pub type ClaimGraph = Graph<usize, usize, Undirected>;
 
 #[derive(PartialOrd, PartialEq, Eq, Ord, Hash)]
 pub struct Rectangle {
@@ -10,6 +17,25 @@ pub struct Rectangle {
     height: usize,
 }
 
+impl Rectangle {
+    fn right(&self) -> usize {
+        self.left + self.width
+    }
+
+    fn bottom(&self) -> usize {
+        self.top + self.height
+    }
+
+    pub fn overlap_area(&self, other: &Rectangle) -> usize {
+        let width = self.right().min(other.right()).saturating_sub(self.left.max(other.left));
+        let height = self
+            .bottom()
+            .min(other.bottom())
+            .saturating_sub(self.top.max(other.top));
+        width * height
+    }
+}
+
 fn parse_claim(line: &str) -> Rectangle {
     let (id, loc_dims) = line.split('@').collect_tuple().unwrap();
     let id = id.split('#').collect::<Vec<_>>()[1]
@@ -45,7 +71,7 @@ pub fn generate_day3(input: &str) -> Vec<Rectangle> {
         .collect()
 }
 
-fn get_num_claims_per_square(rectangles: &[Rectangle]) -> Array2<usize> {
+pub fn get_num_claims_per_square(rectangles: &[Rectangle]) -> Array2<usize> {
     let (mut max_x, mut max_y) = (0, 0);
     let (mut rect_x, mut rect_y);
     for rectangle in rectangles {
@@ -69,25 +95,111 @@ fn get_num_claims_per_square(rectangles: &[Rectangle]) -> Array2<usize> {
     claims
 }
 
+pub fn get_overlapped_area(rectangles: &[Rectangle]) -> usize {
+    let ys: Vec<_> = rectangles
+        .iter()
+        .flat_map(|r| vec![r.top, r.bottom()])
+        .sorted()
+        .into_iter()
+        .dedup()
+        .collect();
+    let y_index: HashMap<_, _> = ys.iter().enumerate().map(|(i, y)| (*y, i)).collect();
+    let events = rectangles
+        .iter()
+        .filter(|r| r.width > 0 && r.height > 0)
+        .flat_map(|r| vec![(r.left, 1, r), (r.right(), -1, r)])
+        .sorted_by_key(|(x, delta, _)| (*x, *delta));
+    let mut counts = vec![0isize; ys.len()];
+    let (mut area, mut previous_x) = (0, 0);
+    for (x, delta, rectangle) in events {
+        let covered: usize = (0..ys.len().saturating_sub(1))
+            .filter(|i| counts[*i] >= 2)
+            .map(|i| ys[i + 1] - ys[i])
+            .sum();
+        area += covered * (x - previous_x);
+        previous_x = x;
+        for count in &mut counts[y_index[&rectangle.top]..y_index[&rectangle.bottom()]] {
+            *count += delta;
+        }
+    }
+    area
+}
+
+pub fn get_intersections(rectangles: &[Rectangle]) -> HashMap<usize, Vec<usize>> {
+    let mut intersections: HashMap<_, _> = rectangles.iter().map(|r| (r.id, Vec::new())).collect();
+    let mut active: Vec<&Rectangle> = Vec::new();
+    for rectangle in rectangles.iter().sorted_by_key(|r| r.left) {
+        active.retain(|a| a.right() > rectangle.left);
+        for other in &active {
+            if rectangle.overlap_area(other) > 0 {
+                intersections.get_mut(&rectangle.id).unwrap().push(other.id);
+                intersections.get_mut(&other.id).unwrap().push(rectangle.id);
+            }
+        }
+        active.push(rectangle);
+    }
+    intersections
+}
+
+pub fn get_non_overlapping(rectangles: &[Rectangle]) -> Vec<usize> {
+    get_intersections(rectangles)
+        .into_iter()
+        .filter(|(_, others)| others.is_empty())
+        .map(|(id, _)| id)
+        .sorted()
+}
+
+pub fn get_overlap_graph(rectangles: &[Rectangle]) -> ClaimGraph {
+    let mut graph = Graph::new_undirected();
+    let nodes: HashMap<_, _> = rectangles
+        .iter()
+        .map(|r| (r.id, graph.add_node(r.id)))
+        .collect();
+    let claims: HashMap<_, _> = rectangles.iter().map(|r| (r.id, r)).collect();
+    let intersections = get_intersections(rectangles);
+    for rectangle in rectangles {
+        for other in intersections[&rectangle.id].iter().filter(|o| **o > rectangle.id) {
+            graph.add_edge(
+                nodes[&rectangle.id],
+                nodes[other],
+                rectangle.overlap_area(claims[other]),
+            );
+        }
+    }
+    graph
+}
+
+pub fn get_conflict_groups(graph: &ClaimGraph) -> Vec<Vec<usize>> {
+    condensation(graph.clone(), true)
+        .into_nodes_edges()
+        .0
+        .into_iter()
+        .map(|node| node.weight.into_iter().sorted())
+        .filter(|group| group.len() > 1)
+        .sorted()
+}
+
+pub fn to_dot(graph: &ClaimGraph) -> String {
+    format!("{}", Dot::new(graph))
+}
+
+pub fn render_overlaps(rectangles: &[Rectangle]) -> Image {
+    let claims = get_num_claims_per_square(rectangles);
+    let (width, height) = claims.dim();
+    let max_claims = claims.iter().cloned().max().unwrap_or(0);
+    let mut image = Image::new(width, height);
+    for ((x, y), num_claims) in claims.indexed_iter() {
+        image.set(x, y, heat_colour(*num_claims, max_claims));
+    }
+    image
+}
+
 #[aoc(day3, part1)]
 pub fn solve_day3_part1(input: &[Rectangle]) -> usize {
-    get_num_claims_per_square(input)
-        .iter()
-        .filter(|num_ids| **num_ids >= 2)
-        .count()
+    get_overlapped_area(input)
 }
 
 #[aoc(day3, part2)]
 pub fn solve_day3_part2(input: &[Rectangle]) -> usize {
-    let claims = get_num_claims_per_square(input);
-    for rectangle in input {
-        if claims.slice(s![
-            rectangle.left..rectangle.left + rectangle.width,
-            rectangle.top..rectangle.top + rectangle.height
-        ]) == Array2::ones((rectangle.width, rectangle.height))
-        {
-            return rectangle.id;
-        }
-    }
-    0
+    get_non_overlapping(input).into_iter().next().unwrap_or(0)
 }
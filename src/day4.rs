@@ -1,41 +1,76 @@
-use chrono::{DateTime, Duration, TimeZone, Timelike, Utc};
+use chrono::{Duration, NaiveDateTime, Timelike};
 use hashbrown::HashMap;
 use itertools::Itertools;
 use ndarray::{Array2, Axis};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    BeginsShift(usize),
+    FallsAsleep,
+    WakesUp,
+}
 
 pub struct Entry {
-    guard_id: Option<usize>,
-    date: DateTime<Utc>,
-    minute: usize,
-    is_asleep: bool,
+    time: NaiveDateTime,
+    event: Event,
+}
+
+pub struct Shift {
+    pub guard_id: usize,
+    pub sleeps: Vec<(NaiveDateTime, NaiveDateTime)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShiftError {
+    NoGuard { time: NaiveDateTime },
+    AsleepTwice { guard_id: usize, time: NaiveDateTime },
+    WakeWithoutSleep { guard_id: usize, time: NaiveDateTime },
+    MissingWake { guard_id: usize, time: NaiveDateTime },
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShiftError::NoGuard { time } => write!(f, "[{}] Event before any shift began", time),
+            ShiftError::AsleepTwice { guard_id, time } => {
+                write!(f, "[{}] Guard #{} fell asleep while asleep", time, guard_id)
+            }
+            ShiftError::WakeWithoutSleep { guard_id, time } => {
+                write!(f, "[{}] Guard #{} woke up while awake", time, guard_id)
+            }
+            ShiftError::MissingWake { guard_id, time } => write!(
+                f,
+                "[{}] Guard #{} fell asleep and never woke up",
+                time, guard_id
+            ),
+        }
+    }
 }
 
+impl Error for ShiftError {}
+
 fn parse_line(line: &str) -> Entry {
     let (date_time, entry) = line.split("] ").collect_tuple().unwrap();
-    let mut date = Utc
-        .datetime_from_str(&date_time[1..], "%Y-%m-%d %H:%M")
-        .unwrap();
-    if date.hour() > 0 {
-        date = (date + Duration::days(1)).date().and_hms(0, 0, 0);
-    }
-    let guard_id = if entry.contains('#') {
-        Some(
+    let time = NaiveDateTime::parse_from_str(&date_time[1..], "%Y-%m-%d %H:%M").unwrap();
+    let event = if entry == "falls asleep" {
+        Event::FallsAsleep
+    } else if entry == "wakes up" {
+        Event::WakesUp
+    } else if entry.starts_with("Guard #") && entry.ends_with(" begins shift") {
+        Event::BeginsShift(
             entry
                 .split(' ')
-                .filter(|x| x.contains('#'))
+                .filter(|x| x.starts_with('#'))
                 .map(|x| x[1..].parse::<usize>().unwrap())
                 .next()
                 .unwrap(),
         )
     } else {
-        None
+        panic!("Weird event: {}", entry)
     };
-    Entry {
-        guard_id,
-        minute: date.minute() as usize,
-        date,
-        is_asleep: entry.contains("asleep"),
-    }
+    Entry { time, event }
 }
 
 #[aoc_generator(day4)]
@@ -44,27 +79,77 @@ pub fn generate_day4(input: &str) -> Vec<Entry> {
         .split('\n')
         .filter(|line| !line.is_empty())
         .map(|line| parse_line(line))
-        .sorted_by(|a, b| (a.date).cmp(&(b.date)))
-}
-
-fn get_matrix(entries: &[Entry]) -> (HashMap<usize, Vec<usize>>, Array2<usize>) {
-    let mut guard_to_date = HashMap::new();
-    let (min_date, max_date) = (entries[0].date, entries[entries.len() - 1].date);
-    let mut times = Array2::zeros(((max_date - min_date).num_days() as usize + 1, 60));
-    for i in 0..entries.len() {
-        let days = (entries[i].date - min_date).num_days() as usize;
-        if let Some(guard_id) = entries[i].guard_id {
-            guard_to_date
-                .entry(guard_id)
-                .or_insert_with(Vec::new)
-                .push(days);
-        } else if i < entries.len() - 1 && entries[i].is_asleep && !entries[i + 1].is_asleep {
-            times
-                .slice_mut(s![days, entries[i].minute..entries[i + 1].minute])
-                .fill(1);
+        .sorted_by(|a, b| (a.time).cmp(&(b.time)))
+}
+
+pub fn get_shifts(entries: &[Entry]) -> Result<Vec<Shift>, ShiftError> {
+    let mut shifts = Vec::new();
+    let (mut current, mut asleep_since): (Option<Shift>, Option<NaiveDateTime>) = (None, None);
+    for entry in entries {
+        match (entry.event, current.as_mut()) {
+            (Event::BeginsShift(guard_id), _) => {
+                if let (Some(shift), Some(time)) = (current.as_ref(), asleep_since) {
+                    return Err(ShiftError::MissingWake {
+                        guard_id: shift.guard_id,
+                        time,
+                    });
+                }
+                shifts.extend(current.take());
+                current = Some(Shift {
+                    guard_id,
+                    sleeps: Vec::new(),
+                });
+            }
+            (_, None) => return Err(ShiftError::NoGuard { time: entry.time }),
+            (Event::FallsAsleep, Some(shift)) => {
+                if asleep_since.is_some() {
+                    return Err(ShiftError::AsleepTwice {
+                        guard_id: shift.guard_id,
+                        time: entry.time,
+                    });
+                }
+                asleep_since = Some(entry.time);
+            }
+            (Event::WakesUp, Some(shift)) => match asleep_since.take() {
+                Some(time) => shift.sleeps.push((time, entry.time)),
+                None => {
+                    return Err(ShiftError::WakeWithoutSleep {
+                        guard_id: shift.guard_id,
+                        time: entry.time,
+                    })
+                }
+            },
         }
     }
-    (guard_to_date, times)
+    if let (Some(shift), Some(time)) = (current.as_ref(), asleep_since) {
+        return Err(ShiftError::MissingWake {
+            guard_id: shift.guard_id,
+            time,
+        });
+    }
+    shifts.extend(current);
+    Ok(shifts)
+}
+
+// one row per shift and one column per minute of the hour,
+// sleeps longer than an hour count every time they pass a minute
+fn get_matrix(shifts: &[Shift]) -> (HashMap<usize, Vec<usize>>, Array2<usize>) {
+    let mut guard_to_shifts = HashMap::new();
+    let mut times = Array2::zeros((shifts.len(), 60));
+    for (i, shift) in shifts.iter().enumerate() {
+        guard_to_shifts
+            .entry(shift.guard_id)
+            .or_insert_with(Vec::new)
+            .push(i);
+        for (start, end) in &shift.sleeps {
+            let mut time = *start;
+            while time < *end {
+                times[[i, time.minute() as usize]] += 1;
+                time += Duration::minutes(1);
+            }
+        }
+    }
+    (guard_to_shifts, times)
 }
 
 fn get_max_value_index(array: &Array2<usize>) -> (usize, usize) {
@@ -74,26 +159,149 @@ fn get_max_value_index(array: &Array2<usize>) -> (usize, usize) {
         .unwrap()
 }
 
-#[aoc(day4, part1)]
-pub fn solve_day4_part1(input: &[Entry]) -> usize {
-    let (guard_to_date, times) = get_matrix(input);
-    let (_, max_guard) = guard_to_date
+fn get_longest_run<I: Iterator<Item = bool>>(values: I) -> usize {
+    let (mut longest, mut current) = (0, 0);
+    for value in values {
+        current = if value { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+pub struct GuardStats {
+    pub guard_id: usize,
+    pub num_shifts: usize,
+    pub total_minutes: usize,
+    pub histogram: Vec<usize>,
+    pub best_minute: Option<usize>,
+    pub best_minute_probability: f64,
+    pub longest_sleep: usize,
+    pub longest_shift_streak: usize,
+}
+
+pub fn get_guard_stats(shifts: &[Shift]) -> Vec<GuardStats> {
+    let (guard_to_shifts, times) = get_matrix(shifts);
+    guard_to_shifts
+        .iter()
+        .map(|(guard_id, indices)| {
+            let guard_times = times.select(Axis(0), indices);
+            let best_minute = match get_max_value_index(&guard_times) {
+                (0, _) => None,
+                (_, minute) => Some(minute),
+            };
+            let sleep_lengths: Vec<_> = indices
+                .iter()
+                .flat_map(|i| shifts[*i].sleeps.iter())
+                .map(|(start, end)| (*end - *start).num_minutes() as usize)
+                .collect();
+            let num_asleep_at_best = best_minute.map_or(0, |minute| {
+                guard_times
+                    .column(minute)
+                    .iter()
+                    .filter(|x| **x > 0)
+                    .count()
+            });
+            GuardStats {
+                guard_id: *guard_id,
+                num_shifts: indices.len(),
+                total_minutes: sleep_lengths.iter().sum(),
+                histogram: (0..60).map(|m| guard_times.column(m).scalar_sum()).collect(),
+                best_minute,
+                best_minute_probability: num_asleep_at_best as f64 / indices.len() as f64,
+                longest_sleep: sleep_lengths.into_iter().max().unwrap_or(0),
+                longest_shift_streak: get_longest_run(
+                    indices.iter().map(|i| !shifts[*i].sleeps.is_empty()),
+                ),
+            }
+        })
+        .sorted_by_key(|stats| stats.guard_id)
+}
+
+pub trait Strategy {
+    fn score(&self, stats: &GuardStats) -> usize;
+}
+
+pub struct MostMinutesAsleep;
+
+impl Strategy for MostMinutesAsleep {
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.total_minutes
+    }
+}
+
+pub struct MostFrequentMinute;
+
+impl Strategy for MostFrequentMinute {
+    fn score(&self, stats: &GuardStats) -> usize {
+        stats.best_minute.map_or(0, |minute| stats.histogram[minute])
+    }
+}
+
+pub fn choose_guard<'a, S: Strategy>(stats: &'a [GuardStats], strategy: &S) -> &'a GuardStats {
+    stats
+        .iter()
+        .max_by_key(|s| (strategy.score(s), s.guard_id))
+        .unwrap()
+}
+
+pub fn to_csv(stats: &[GuardStats]) -> String {
+    let mut csv = format!("guard,{}\n", (0..60).join(","));
+    for guard in stats {
+        csv.push_str(&format!(
+            "{},{}\n",
+            guard.guard_id,
+            guard.histogram.iter().join(",")
+        ));
+    }
+    csv
+}
+
+pub fn to_heatmap(stats: &[GuardStats]) -> String {
+    let shades = b" .:-=+*#%@";
+    let max_count = stats
+        .iter()
+        .flat_map(|s| s.histogram.iter())
+        .cloned()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let width = stats
         .iter()
-        .map(|(g_id, dates)| (times.select(Axis(0), &dates).scalar_sum(), g_id))
+        .map(|s| s.guard_id.to_string().len())
         .max()
-        .unwrap();
-    let (_max_count, max_minute) =
-        get_max_value_index(&times.select(Axis(0), &guard_to_date[&max_guard]));
-    max_minute * max_guard
+        .unwrap_or(0);
+    let mut heatmap = format!(
+        "{:>width$} |{}\n",
+        "",
+        (0..60).map(|m| if m % 10 == 0 { '|' } else { ' ' }).collect::<String>(),
+        width = width + 1
+    );
+    for guard in stats {
+        let row: String = guard
+            .histogram
+            .iter()
+            .map(|c| shades[c * (shades.len() - 1) / max_count] as char)
+            .collect();
+        heatmap.push_str(&format!(
+            "#{:>width$} |{}\n",
+            guard.guard_id,
+            row,
+            width = width
+        ));
+    }
+    heatmap
+}
+
+#[aoc(day4, part1)]
+pub fn solve_day4_part1(input: &[Entry]) -> usize {
+    let stats = get_guard_stats(&get_shifts(input).unwrap());
+    let guard = choose_guard(&stats, &MostMinutesAsleep);
+    guard.guard_id * guard.best_minute.unwrap()
 }
 
 #[aoc(day4, part2)]
 pub fn solve_day4_part2(input: &[Entry]) -> usize {
-    let (guard_to_date, times) = get_matrix(input);
-    let ((_max_count, max_minute), max_guard) = guard_to_date
-        .iter()
-        .map(|(g_id, indices)| (get_max_value_index(&times.select(Axis(0), indices)), g_id))
-        .max()
-        .unwrap();
-    max_minute * max_guard
+    let stats = get_guard_stats(&get_shifts(input).unwrap());
+    let guard = choose_guard(&stats, &MostFrequentMinute);
+    guard.guard_id * guard.best_minute.unwrap()
 }
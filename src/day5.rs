@@ -1,20 +1,140 @@
+use hashbrown::HashMap;
+use std::sync::Arc;
+use std::thread;
+
 #[inline]
 fn is_opposite_polarity(c1: char, c2: char) -> bool {
     (c1 != c2) && (c1.to_ascii_lowercase() == c2.to_ascii_lowercase())
 }
 
-fn react_polymer(input: &str, minus_letter: Option<char>) -> usize {
-    let mut output = Vec::new();
-    for character in input.trim().chars() {
-        if Some(character.to_ascii_lowercase()) == minus_letter {
-            // ignore letter
-        } else if !output.is_empty() && is_opposite_polarity(character, *output.last().unwrap()) {
-            output.pop();
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reaction {
+    Inert,
+    Annihilate,
+    Produce(u8),
+}
+
+pub trait ReactionRule {
+    fn react(&self, left: u8, right: u8) -> Reaction;
+}
+
+// rules whose result doesn't depend on the order reactions happen in,
+// which is what lets chunks of a polymer be reduced independently
+pub trait ConfluentRule: ReactionRule {}
+
+pub struct OppositePolarity;
+
+impl ReactionRule for OppositePolarity {
+    fn react(&self, left: u8, right: u8) -> Reaction {
+        if left != right && left.eq_ignore_ascii_case(&right) {
+            Reaction::Annihilate
         } else {
-            output.push(character);
+            Reaction::Inert
+        }
+    }
+}
+
+impl ConfluentRule for OppositePolarity {}
+
+// reactions are directional, (left, right) only fires when left comes first
+#[derive(Default)]
+pub struct ReactionTable {
+    reactions: HashMap<(u8, u8), Reaction>,
+}
+
+impl ReactionTable {
+    pub fn new() -> Self {
+        ReactionTable::default()
+    }
+
+    pub fn annihilate(mut self, left: u8, right: u8) -> Self {
+        self.reactions.insert((left, right), Reaction::Annihilate);
+        self
+    }
+
+    pub fn produce(mut self, left: u8, right: u8, product: u8) -> Self {
+        self.reactions
+            .insert((left, right), Reaction::Produce(product));
+        self
+    }
+}
+
+impl ReactionRule for ReactionTable {
+    fn react(&self, left: u8, right: u8) -> Reaction {
+        *self
+            .reactions
+            .get(&(left, right))
+            .unwrap_or(&Reaction::Inert)
+    }
+}
+
+// eliminated only counts units from the input, products are counted separately
+#[derive(Default)]
+pub struct Polymer {
+    pub units: Vec<u8>,
+    pub eliminated: HashMap<u8, usize>,
+    pub produced: HashMap<u8, usize>,
+    from_input: Vec<bool>,
+}
+
+impl Polymer {
+    fn eliminate(&mut self, unit: u8, from_input: bool) {
+        if from_input {
+            *self.eliminated.entry(unit).or_insert(0) += 1;
+        }
+    }
+
+    fn push<R: ReactionRule>(&mut self, rule: &R, unit: u8, from_input: bool) {
+        let (mut unit, mut from_input) = (unit, from_input);
+        loop {
+            let reaction = match self.units.last() {
+                Some(last) => rule.react(*last, unit),
+                None => Reaction::Inert,
+            };
+            if reaction == Reaction::Inert {
+                self.units.push(unit);
+                self.from_input.push(from_input);
+                return;
+            }
+            let (last, last_from_input) =
+                (self.units.pop().unwrap(), self.from_input.pop().unwrap());
+            self.eliminate(last, last_from_input);
+            self.eliminate(unit, from_input);
+            match reaction {
+                Reaction::Produce(product) => {
+                    *self.produced.entry(product).or_insert(0) += 1;
+                    unit = product;
+                    from_input = false;
+                }
+                _ => return,
+            }
         }
     }
-    output.len()
+}
+
+pub fn reduce_polymer<R, I>(rule: &R, units: I) -> Polymer
+where
+    R: ReactionRule,
+    I: IntoIterator<Item = u8>,
+{
+    let mut polymer = Polymer::default();
+    for unit in units {
+        polymer.push(rule, unit, true);
+    }
+    polymer
+}
+
+fn merge_reduced<R: ConfluentRule>(rule: &R, mut left: Polymer, right: Polymer) -> Polymer {
+    for (unit, count) in right.eliminated {
+        *left.eliminated.entry(unit).or_insert(0) += count;
+    }
+    for (unit, count) in right.produced {
+        *left.produced.entry(unit).or_insert(0) += count;
+    }
+    for (unit, from_input) in right.units.into_iter().zip(right.from_input) {
+        left.push(rule, unit, from_input);
+    }
+    left
 }
 
 #[allow(dead_code)]
@@ -45,15 +165,55 @@ fn react_polymer_slow(input: &str) -> usize {
     input.len()
 }
 
+pub fn reduce_polymer_parallel<R>(rule: Arc<R>, units: &[u8], num_threads: usize) -> Polymer
+where
+    R: ConfluentRule + Send + Sync + 'static,
+{
+    let chunk_size = (units.len() / num_threads.max(1)).max(1);
+    let handles: Vec<_> = units
+        .chunks(chunk_size)
+        .map(|chunk| {
+            let (rule, chunk) = (Arc::clone(&rule), chunk.to_vec());
+            thread::spawn(move || reduce_polymer(&*rule, chunk))
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .fold(Polymer::default(), |left, right| {
+            merge_reduced(&*rule, left, right)
+        })
+}
+
+const NUM_THREADS: usize = 4;
+
 #[aoc(day5, part1)]
 pub fn solve_day5_part1(input: &str) -> usize {
-    react_polymer(input, None)
+    reduce_polymer_parallel(
+        Arc::new(OppositePolarity),
+        input.trim().as_bytes(),
+        NUM_THREADS,
+    )
+    .units
+    .len()
 }
 
 #[aoc(day5, part2)]
 pub fn solve_day5_part2(input: &str) -> usize {
+    let reduced = reduce_polymer_parallel(
+        Arc::new(OppositePolarity),
+        input.trim().as_bytes(),
+        NUM_THREADS,
+    );
     (b'a'..=b'z')
-        .map(|a| react_polymer(input, Some(a as char)))
+        .map(|a| {
+            let units = reduced
+                .units
+                .iter()
+                .cloned()
+                .filter(|u| u.to_ascii_lowercase() != a);
+            reduce_polymer(&OppositePolarity, units).units.len()
+        })
         .min()
         .unwrap()
 }
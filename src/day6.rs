@@ -1,4 +1,8 @@
+use hashbrown::HashSet;
+use image::{label_colour, Image};
 use itertools::Itertools;
+use ndarray::Array2;
+use std::cmp::Ordering;
 
 type Grid = (Vec<(usize, usize)>, usize, usize);
 
@@ -27,48 +31,442 @@ fn generator_day6(input: &str) -> Box<Grid> {
     Box::new((coords, m_x, m_y))
 }
 
-fn manhattan_distance(coord_1: (usize, usize), coord_2: (usize, usize)) -> usize {
-    ((coord_1.0 as isize - coord_2.0 as isize).abs()
-        + (coord_1.1 as isize - coord_2.1 as isize).abs()) as usize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
 }
 
-#[aoc(day6, part1)]
-pub fn solve_day6_part1(input: &Grid) -> i32 {
-    let (coords, m_x, m_y) = input;
-    let (m_x, m_y) = (*m_x, *m_y);
-    let mut areas = vec![0; coords.len()];
-    for (i, j) in iproduct!(0..m_x, 0..m_y) {
-        let distances = coords
-            .iter()
-            .enumerate()
-            .map(|(index, coord)| (manhattan_distance(*coord, (i, j)), index))
-            .sorted();
-        if distances[0].0 != distances[1].0 {
-            areas[distances[0].1] += 1;
-            if i == 0 || i == m_x - 1 || j == 0 || j == m_y - 1 {
-                areas[distances[0].1] = ::std::i32::MIN;
+impl Metric {
+    // Euclidean distances are left squared so that they stay integral
+    pub fn distance(&self, coord_1: (isize, isize), coord_2: (isize, isize)) -> isize {
+        let (d_x, d_y) = ((coord_1.0 - coord_2.0).abs(), (coord_1.1 - coord_2.1).abs());
+        match self {
+            Metric::Manhattan => d_x + d_y,
+            Metric::Chebyshev => d_x.max(d_y),
+            Metric::Euclidean => d_x * d_x + d_y * d_y,
+        }
+    }
+
+    fn neighbors(&self, (x, y): (isize, isize)) -> Vec<(isize, isize)> {
+        match self {
+            Metric::Manhattan => vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)],
+            _ => iproduct!(-1..=1, -1..=1)
+                .filter(|d| *d != (0, 0))
+                .map(|(d_x, d_y)| (x + d_x, y + d_y))
+                .collect(),
+        }
+    }
+}
+
+pub struct RegionMap {
+    pub origin: (isize, isize),
+    pub labels: Array2<Option<usize>>,
+}
+
+// unbounded regions have no area
+#[derive(Debug, PartialEq, Eq)]
+pub struct Region {
+    pub area: Option<usize>,
+    pub bounded: bool,
+}
+
+fn get_nearest(
+    coords: &[(isize, isize)],
+    candidates: &[usize],
+    metric: Metric,
+    point: (isize, isize),
+) -> Vec<usize> {
+    let distances: Vec<_> = candidates
+        .iter()
+        .map(|c| (metric.distance(coords[*c], point), *c))
+        .sorted();
+    distances
+        .iter()
+        .take_while(|(d, _)| *d == distances[0].0)
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+fn on_hull(coords: &[(isize, isize)]) -> HashSet<usize> {
+    let cross = |o: (isize, isize), a: (isize, isize), b: (isize, isize)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let order = (0..coords.len()).sorted_by_key(|i| coords[*i]);
+    let mut hull = HashSet::new();
+    for pass in &[order.clone(), order.into_iter().rev().collect()] {
+        let mut chain: Vec<usize> = Vec::new();
+        for i in pass {
+            // popping only on strict right turns keeps collinear points on the hull
+            while chain.len() >= 2
+                && cross(
+                    coords[chain[chain.len() - 2]],
+                    coords[chain[chain.len() - 1]],
+                    coords[*i],
+                ) < 0
+            {
+                chain.pop();
             }
+            chain.push(*i);
         }
+        hull.extend(chain);
     }
-    areas.into_iter().max().unwrap()
+    hull
 }
 
-#[aoc(day6, part2)]
-pub fn solve_day6_part2(input: &Grid) -> usize {
-    let (coords, m_x, m_y) = input;
-    let (m_x, m_y) = (*m_x, *m_y);
-    iproduct!(0..=m_x, 0..=m_y)
-        .map(|c| {
-            if coords
+// Manhattan and Chebyshev regions are unbounded iff they own a point on the bounding box
+// edge (in rotated coordinates for Chebyshev), Euclidean ones iff the point is on the hull
+fn get_unbounded(coords: &[(isize, isize)], metric: Metric) -> HashSet<usize> {
+    let transformed: Vec<_> = match metric {
+        Metric::Euclidean => return on_hull(coords),
+        Metric::Manhattan => coords.to_vec(),
+        Metric::Chebyshev => coords.iter().map(|(x, y)| (x + y, x - y)).collect(),
+    };
+    let (min_x, max_x) = transformed
+        .iter()
+        .map(|c| c.0)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let (min_y, max_y) = transformed
+        .iter()
+        .map(|c| c.1)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let all: Vec<_> = (0..coords.len()).collect();
+    (min_x..=max_x)
+        .flat_map(|x| vec![(x, min_y), (x, max_y)])
+        .chain((min_y..=max_y).flat_map(|y| vec![(min_x, y), (max_x, y)]))
+        .map(|point| get_nearest(&transformed, &all, Metric::Manhattan, point))
+        .filter(|nearest| nearest.len() == 1)
+        .map(|nearest| nearest[0])
+        .collect()
+}
+
+fn get_cell_polygon(coords: &[(isize, isize)], index: usize) -> Vec<(f64, f64)> {
+    let (s_x, s_y) = (coords[index].0 as f64, coords[index].1 as f64);
+    // bounded cells have vertices at circumcentres, which lie within ~1.5 * span^3 of the points
+    let span = coords
+        .iter()
+        .flat_map(|(x, y)| vec![*x, *y])
+        .minmax()
+        .into_option()
+        .map_or(0, |(low, high)| high - low) as f64;
+    let far = 4. * (span + 1.).powi(3);
+    let mut polygon = vec![(-far, -far), (far, -far), (far, far), (-far, far)];
+    for (t_x, t_y) in coords.iter().map(|(x, y)| (*x as f64, *y as f64)) {
+        if (t_x, t_y) == (s_x, s_y) {
+            continue;
+        }
+        // keep the half-plane of points closer to s than to t
+        let side = |(x, y): (f64, f64)| {
+            (t_x - s_x) * x + (t_y - s_y) * y - (t_x * t_x + t_y * t_y - s_x * s_x - s_y * s_y) / 2.
+        };
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, point) in polygon.iter().enumerate() {
+            let next = polygon[(i + 1) % polygon.len()];
+            let (side_point, side_next) = (side(*point), side(next));
+            if side_point <= 0. {
+                clipped.push(*point);
+            }
+            if (side_point <= 0.) != (side_next <= 0.) {
+                let t = side_point / (side_point - side_next);
+                clipped.push((
+                    point.0 + t * (next.0 - point.0),
+                    point.1 + t * (next.1 - point.1),
+                ));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+fn get_bounds(polygon: &[(f64, f64)]) -> ((f64, f64), (f64, f64)) {
+    polygon.iter().fold(
+        ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)),
+        |(low, high), (x, y)| {
+            (
+                (low.0.min(*x), low.1.min(*y)),
+                (high.0.max(*x), high.1.max(*y)),
+            )
+        },
+    )
+}
+
+// the window covers every coordinate and every bounded region, except for Euclidean
+// regions which can reach far past the coordinates and are counted from their cells instead
+fn get_window(coords: &[(isize, isize)], metric: Metric) -> ((isize, isize), (isize, isize)) {
+    let (min_x, max_x) = coords.iter().map(|c| c.0).minmax().into_option().unwrap();
+    let (min_y, max_y) = coords.iter().map(|c| c.1).minmax().into_option().unwrap();
+    match metric {
+        Metric::Manhattan | Metric::Euclidean => ((min_x, min_y), (max_x, max_y)),
+        Metric::Chebyshev => {
+            let (min_u, max_u) = coords
                 .iter()
-                .map(|c1| manhattan_distance(*c1, c))
-                .sum::<usize>()
-                < 10000
-            {
-                1
-            } else {
-                0
+                .map(|(x, y)| x + y)
+                .minmax()
+                .into_option()
+                .unwrap();
+            let (min_v, max_v) = coords
+                .iter()
+                .map(|(x, y)| x - y)
+                .minmax()
+                .into_option()
+                .unwrap();
+            (
+                ((min_u + min_v).div_euclid(2), (min_u - max_v).div_euclid(2)),
+                (
+                    (max_u + max_v + 1).div_euclid(2),
+                    (max_u - min_v + 1).div_euclid(2),
+                ),
+            )
+        }
+    }
+}
+
+fn count_cell_points(coords: &[(isize, isize)], index: usize) -> usize {
+    let polygon = get_cell_polygon(coords, index);
+    let ((_, min_y), (_, max_y)) = get_bounds(&polygon);
+    let all: Vec<_> = (0..coords.len()).collect();
+    let is_owned =
+        |x: isize, y: isize| get_nearest(coords, &all, Metric::Euclidean, (x, y)) == vec![index];
+    let mut count = 0;
+    for y in (min_y.floor() as isize)..=(max_y.ceil() as isize) {
+        let row = y as f64;
+        let crossings: Vec<_> = (0..polygon.len())
+            .map(|i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+            .filter(|(a, b)| (a.1 - row) * (b.1 - row) <= 0.)
+            .map(|(a, b)| {
+                if a.1 == b.1 {
+                    (a.0, row)
+                } else {
+                    (a.0 + (row - a.1) / (b.1 - a.1) * (b.0 - a.0), row)
+                }
+            })
+            .collect();
+        if crossings.is_empty() {
+            continue;
+        }
+        let ((left, _), (right, _)) = get_bounds(&crossings);
+        // points a unit inside the row are owned, the ones near its ends are checked exactly,
+        // as are whole rows along the top or bottom of the cell which may lie on its boundary
+        let (start, end) = (left.ceil() as isize + 1, right.floor() as isize - 1);
+        let on_edge = (row - min_y).abs() < 1e-3 || (max_y - row).abs() < 1e-3;
+        let edge_points: Vec<_> = if start <= end && !on_edge {
+            count += (end - start + 1) as usize;
+            (start - 2..start).chain(end + 1..end + 3).collect()
+        } else {
+            (start - 2..end + 3).collect()
+        };
+        count += edge_points.into_iter().filter(|x| is_owned(*x, y)).count();
+    }
+    count
+}
+
+fn fill_window(
+    coords: &[(isize, isize)],
+    metric: Metric,
+    origin: (isize, isize),
+    shape: (usize, usize),
+) -> Array2<Option<usize>> {
+    let mut nearest: Array2<Vec<usize>> = Array2::from_elem(shape, Vec::new());
+    let mut queued = Array2::from_elem(shape, false);
+    let to_index = |(x, y): (isize, isize)| {
+        let (i, j) = (x - origin.0, y - origin.1);
+        if i >= 0 && j >= 0 && (i as usize) < shape.0 && (j as usize) < shape.1 {
+            Some([i as usize, j as usize])
+        } else {
+            None
+        }
+    };
+    let cell_boxes: Vec<_> = if metric == Metric::Euclidean {
+        (0..coords.len())
+            .map(|i| {
+                let ((min_x, min_y), (max_x, max_y)) = get_bounds(&get_cell_polygon(coords, i));
+                ((min_x - 1., min_y - 1.), (max_x + 1., max_y + 1.))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut frontier: Vec<_> = coords.iter().cloned().unique().collect();
+    for coord in &frontier {
+        let index = to_index(*coord).unwrap();
+        nearest[index] = (0..coords.len()).filter(|i| coords[*i] == *coord).collect();
+        queued[index] = true;
+    }
+    while !frontier.is_empty() {
+        let mut layer = Vec::new();
+        for cell in &frontier {
+            for neighbor in metric.neighbors(*cell) {
+                if let Some(index) = to_index(neighbor) {
+                    if !queued[index] {
+                        queued[index] = true;
+                        layer.push(neighbor);
+                    }
+                }
             }
+        }
+        let layer_nearest: Vec<_> = layer
+            .iter()
+            .map(|cell| {
+                // a cell's nearest coordinate is nearest to a neighbour one step closer to it.
+                // Euclidean regions can be disconnected on the grid so that doesn't hold,
+                // instead only coordinates whose exact Voronoi cell covers the point are checked
+                let candidates: Vec<_> = if metric == Metric::Euclidean {
+                    let (x, y) = (cell.0 as f64, cell.1 as f64);
+                    (0..coords.len())
+                        .filter(|i| {
+                            let (low, high) = cell_boxes[*i];
+                            low.0 <= x && x <= high.0 && low.1 <= y && y <= high.1
+                        })
+                        .collect()
+                } else {
+                    metric
+                        .neighbors(*cell)
+                        .into_iter()
+                        .filter_map(to_index)
+                        .flat_map(|index| nearest[index].iter().cloned())
+                        .unique()
+                        .collect()
+                };
+                get_nearest(coords, &candidates, metric, *cell)
+            })
+            .collect();
+        for (cell, cell_nearest) in layer.iter().zip(layer_nearest) {
+            nearest[to_index(*cell).unwrap()] = cell_nearest;
+        }
+        frontier = layer;
+    }
+    nearest.map(|n| if n.len() == 1 { Some(n[0]) } else { None })
+}
+
+pub fn get_regions(coords: &[(usize, usize)], metric: Metric) -> (RegionMap, Vec<Region>) {
+    let coords: Vec<_> = coords
+        .iter()
+        .map(|(x, y)| (*x as isize, *y as isize))
+        .collect();
+    let unbounded = get_unbounded(&coords, metric);
+    let (origin, corner) = get_window(&coords, metric);
+    let shape = (
+        (corner.0 - origin.0 + 1) as usize,
+        (corner.1 - origin.1 + 1) as usize,
+    );
+    let labels = fill_window(&coords, metric, origin, shape);
+    let mut areas = vec![0; coords.len()];
+    if metric == Metric::Euclidean {
+        for i in (0..coords.len()).filter(|i| !unbounded.contains(i)) {
+            areas[i] = count_cell_points(&coords, i);
+        }
+    } else {
+        for label in labels.iter().filter_map(|l| *l) {
+            areas[label] += 1;
+        }
+    }
+    let regions = areas
+        .into_iter()
+        .enumerate()
+        .map(|(i, area)| Region {
+            area: if unbounded.contains(&i) {
+                None
+            } else {
+                Some(area)
+            },
+            bounded: !unbounded.contains(&i),
+        })
+        .collect();
+    (RegionMap { origin, labels }, regions)
+}
+
+pub fn render_regions(map: &RegionMap) -> Image {
+    let (width, height) = map.labels.dim();
+    let mut image = Image::new(width, height);
+    for ((x, y), label) in map.labels.indexed_iter() {
+        image.set(x, y, label.map_or([40, 40, 40], label_colour));
+    }
+    image
+}
+
+#[aoc(day6, part1)]
+pub fn solve_day6_part1(input: &Grid) -> usize {
+    let (_, regions) = get_regions(&input.0, Metric::Manhattan);
+    regions.into_iter().filter_map(|r| r.area).max().unwrap()
+}
+
+fn get_axis_sum(values: &[isize], position: isize) -> usize {
+    values.iter().map(|v| (v - position).unsigned_abs()).sum()
+}
+
+// positions along one axis whose distance sum stays under the limit, with their sums
+fn get_axis_sums(values: &[isize], limit: usize) -> Vec<(isize, usize)> {
+    let median = values.iter().cloned().sorted()[values.len() / 2];
+    let within = |position: &isize| get_axis_sum(values, *position) < limit;
+    let low = (0..)
+        .map(|offset| median - offset)
+        .take_while(&within)
+        .last();
+    let high = (0..)
+        .map(|offset| median + offset)
+        .take_while(&within)
+        .last();
+    match (low, high) {
+        (Some(low), Some(high)) => (low..=high)
+            .map(|position| (position, get_axis_sum(values, position)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub struct SafeRegion {
+    pub size: usize,
+    pub extent: Option<((isize, isize), (isize, isize))>,
+}
+
+// the total Manhattan distance splits into independent sums over x and y,
+// so the region is found from the two axes without scanning a fixed window
+pub fn get_safe_region(coords: &[(usize, usize)], threshold: usize) -> SafeRegion {
+    let xs: Vec<_> = coords.iter().map(|c| c.0 as isize).collect();
+    let ys: Vec<_> = coords.iter().map(|c| c.1 as isize).collect();
+    let min_y_sum = get_axis_sum(&ys, ys.iter().cloned().sorted()[ys.len() / 2]);
+    let min_x_sum = get_axis_sum(&xs, xs.iter().cloned().sorted()[xs.len() / 2]);
+    if min_x_sum + min_y_sum >= threshold {
+        return SafeRegion {
+            size: 0,
+            extent: None,
+        };
+    }
+    let x_sums = get_axis_sums(&xs, threshold - min_y_sum);
+    let y_sums = get_axis_sums(&ys, threshold - min_x_sum);
+    let sorted_y_sums: Vec<_> = y_sums.iter().map(|(_, sum)| *sum).sorted();
+    let size = x_sums
+        .iter()
+        .map(|(_, x_sum)| {
+            let limit = threshold - x_sum;
+            // never equal, so the error is the number of sums below the limit
+            sorted_y_sums
+                .binary_search_by(|y_sum| {
+                    if *y_sum < limit {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
+                })
+                .unwrap_err()
         })
-        .sum()
+        .sum();
+    SafeRegion {
+        size,
+        extent: Some((
+            (x_sums[0].0, y_sums[0].0),
+            (x_sums[x_sums.len() - 1].0, y_sums[y_sums.len() - 1].0),
+        )),
+    }
+}
+
+#[aoc(day6, part2)]
+pub fn solve_day6_part2(input: &Grid) -> usize {
+    get_safe_region(&input.0, 10000).size
 }
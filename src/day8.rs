@@ -1,6 +1,37 @@
 use itertools::Itertools;
 use petgraph::graph::NodeIndex;
-use petgraph::Graph;
+use petgraph::visit::{Dfs, DfsPostOrder, EdgeRef};
+use petgraph::{Direction, Graph};
+use std::error::Error;
+use std::fmt;
+
+pub type LicenseTree = Graph<Vec<usize>, usize>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LicenseError {
+    Truncated { position: usize },
+    TrailingInput { position: usize, remaining: usize },
+}
+
+impl fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LicenseError::Truncated { position } => {
+                write!(f, "Input ended at position {} inside a node", position)
+            }
+            LicenseError::TrailingInput {
+                position,
+                remaining,
+            } => write!(
+                f,
+                "{} trailing numbers after the root node ends at position {}",
+                remaining, position
+            ),
+        }
+    }
+}
+
+impl Error for LicenseError {}
 
 #[aoc_generator(day8)]
 pub fn generate_day8(input: &str) -> Vec<usize> {
@@ -10,56 +41,152 @@ pub fn generate_day8(input: &str) -> Vec<usize> {
         .collect()
 }
 
-fn build_tree(
-    tree: &mut Graph<Vec<usize>, ()>,
-    index: usize,
-    numbers: &[usize],
-) -> (NodeIndex<u32>, usize) {
-    let (num_children, num_metadata) = (numbers[index], numbers[index + 1]);
-    let parent = tree.add_node(Vec::with_capacity(num_metadata));
-    let mut index = index + 2;
-    for _ in 0..num_children {
-        let (child, new_index) = build_tree(tree, index, &numbers);
-        index = new_index;
-        tree.add_edge(parent, child, ());
+struct Frame {
+    node: NodeIndex<u32>,
+    num_children: usize,
+    next_child: usize,
+    num_metadata: usize,
+}
+
+pub fn parse_tree<I: IntoIterator<Item = usize>>(
+    numbers: I,
+) -> Result<(LicenseTree, NodeIndex<u32>), LicenseError> {
+    let mut numbers = numbers.into_iter();
+    let mut position = 0;
+    let mut next_number = |position: &mut usize| {
+        let number = numbers.next().ok_or(LicenseError::Truncated {
+            position: *position,
+        });
+        *position += 1;
+        number
+    };
+    let mut tree = LicenseTree::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut root = None;
+    loop {
+        let parent = match stack.last_mut() {
+            None if root.is_some() => break,
+            None => None,
+            Some(frame) if frame.next_child < frame.num_children => {
+                frame.next_child += 1;
+                Some((frame.node, frame.next_child - 1))
+            }
+            Some(_) => {
+                let frame = stack.pop().unwrap();
+                for _ in 0..frame.num_metadata {
+                    let metadata = next_number(&mut position)?;
+                    tree[frame.node].push(metadata);
+                }
+                continue;
+            }
+        };
+        let (num_children, num_metadata) =
+            (next_number(&mut position)?, next_number(&mut position)?);
+        let node = tree.add_node(Vec::with_capacity(num_metadata));
+        match parent {
+            Some((parent, child_index)) => {
+                tree.add_edge(parent, node, child_index);
+            }
+            None => root = Some(node),
+        }
+        stack.push(Frame {
+            node,
+            num_children,
+            next_child: 0,
+            num_metadata,
+        });
     }
-    for _ in 0..num_metadata {
-        tree[parent].push(numbers[index]);
-        index += 1;
+    let remaining = numbers.count();
+    if remaining > 0 {
+        return Err(LicenseError::TrailingInput {
+            position,
+            remaining,
+        });
     }
-    (parent, index)
+    Ok((tree, root.unwrap()))
+}
+
+pub fn get_children(tree: &LicenseTree, index: NodeIndex<u32>) -> Vec<NodeIndex<u32>> {
+    tree.edges(index)
+        .map(|e| (*e.weight(), e.target()))
+        .sorted()
+        .into_iter()
+        .map(|(_, child)| child)
+        .collect()
+}
+
+pub fn get_parent(tree: &LicenseTree, index: NodeIndex<u32>) -> Option<NodeIndex<u32>> {
+    tree.neighbors_directed(index, Direction::Incoming).next()
+}
+
+pub fn get_path(tree: &LicenseTree, index: NodeIndex<u32>) -> Vec<NodeIndex<u32>> {
+    let mut path = vec![index];
+    while let Some(parent) = get_parent(tree, *path.last().unwrap()) {
+        path.push(parent);
+    }
+    path.reverse();
+    path
+}
+
+pub fn get_depth(tree: &LicenseTree, index: NodeIndex<u32>) -> usize {
+    get_path(tree, index).len() - 1
+}
+
+pub fn get_subtree_metadata_sum(tree: &LicenseTree, index: NodeIndex<u32>) -> usize {
+    let mut dfs = Dfs::new(tree, index);
+    let mut total = 0;
+    while let Some(node) = dfs.next(tree) {
+        total += tree[node].iter().sum::<usize>();
+    }
+    total
+}
+
+pub fn serialize_tree(tree: &LicenseTree, root: NodeIndex<u32>) -> Vec<usize> {
+    let mut numbers = Vec::with_capacity(tree.node_count() * 2);
+    let mut stack = vec![(root, get_children(tree, root), 0)];
+    numbers.extend(&[stack[0].1.len(), tree[root].len()]);
+    while let Some((node, children, next_child)) = stack.pop() {
+        if next_child < children.len() {
+            let child = children[next_child];
+            let grandchildren = get_children(tree, child);
+            numbers.extend(&[grandchildren.len(), tree[child].len()]);
+            stack.push((node, children, next_child + 1));
+            stack.push((child, grandchildren, 0));
+        } else {
+            numbers.extend(&tree[node]);
+        }
+    }
+    numbers
 }
 
 #[aoc(day8, part1)]
 pub fn solve_day8_part1(input: &[usize]) -> usize {
-    let mut tree = Graph::new();
-    build_tree(&mut tree, 0, input);
+    let (tree, _) = parse_tree(input.iter().cloned()).unwrap();
     tree.node_indices()
         .map(|n| tree[n].iter().sum::<usize>())
         .sum()
 }
 
-fn get_node_value(tree: &Graph<Vec<usize>, ()>, index: NodeIndex<u32>) -> usize {
-    if tree.neighbors(index).count() == 0 {
-        tree[index].iter().sum()
-    } else {
-        let children = tree.neighbors(index).sorted();
-        tree[index]
-            .iter()
-            .map(|m| {
-                if *m == 0 || *m > children.len() {
-                    0
-                } else {
-                    get_node_value(tree, children[*m - 1])
-                }
-            })
-            .sum()
+pub fn get_node_values(tree: &LicenseTree, root: NodeIndex<u32>) -> Vec<usize> {
+    let mut values = vec![0; tree.node_count()];
+    let mut post_order = DfsPostOrder::new(tree, root);
+    while let Some(node) = post_order.next(tree) {
+        let children = get_children(tree, node);
+        values[node.index()] = if children.is_empty() {
+            tree[node].iter().sum()
+        } else {
+            tree[node]
+                .iter()
+                .filter(|m| **m > 0 && **m <= children.len())
+                .map(|m| values[children[*m - 1].index()])
+                .sum()
+        };
     }
+    values
 }
 
 #[aoc(day8, part2)]
 pub fn solve_day8_part2(input: &[usize]) -> usize {
-    let mut tree = Graph::new();
-    let (root, _) = build_tree(&mut tree, 0, input);
-    get_node_value(&tree, root)
+    let (tree, root) = parse_tree(input.iter().cloned()).unwrap();
+    get_node_values(&tree, root)[root.index()]
 }
@@ -1,6 +1,4 @@
-#![allow(dead_code)]
-use hashbrown::HashMap;
-use itertools::Itertools;
+use std::collections::VecDeque;
 
 #[aoc_generator(day9)]
 pub fn generate_day9(input: &str) -> Box<(usize, usize)> {
@@ -8,18 +6,6 @@ pub fn generate_day9(input: &str) -> Box<(usize, usize)> {
     Box::new((parts[0].parse().unwrap(), parts[6].parse().unwrap()))
 }
 
-#[allow(dead_code)]
-fn get_circular_index(length: usize, offset: isize) -> usize {
-    let new_index = offset;
-    if new_index >= 0 && new_index < length as isize {
-        new_index as usize
-    } else if new_index < 0 {
-        length - (new_index.abs() as usize)
-    } else {
-        (new_index as usize) - length
-    }
-}
-
 //#[aoc(day9, part1)]
 pub fn test_day9(_: &(usize, usize)) -> usize {
     println!("{} 32", solve_day9_part1(&(9, 25)));
@@ -31,141 +17,45 @@ pub fn test_day9(_: &(usize, usize)) -> usize {
     0
 }
 
-pub struct GameCircle {
-    circle: Vec<usize>,
-    num_marbles: usize,
-    num_players: usize,
-    num_marbles_used: usize,
-    current_marble_index: usize,
-    current_player: usize,
-    scores: HashMap<usize, usize>,
+pub struct MarbleGame {
+    pub scores: Vec<usize>,
+    // (marble, player, new score) for every scoring turn
+    pub history: Option<Vec<(usize, usize, usize)>>,
 }
 
-impl GameCircle {
-    fn new(num_players: usize, num_marbles: usize) -> Self {
-        GameCircle {
-            circle: vec![0],
-            num_marbles,
-            num_players,
-            num_marbles_used: 1,
-            current_marble_index: 0,
-            current_player: 1,
-            scores: HashMap::new(),
-        }
-    }
-
-    fn move_player(&mut self, offset: usize) {
-        self.current_player += offset;
-        if self.current_player > self.num_players {
-            self.current_player -= self.num_players;
-        }
-    }
-
-    fn add_score(&mut self, score: usize) {
-        *self.scores.entry(self.current_player).or_insert(0) += score;
-    }
-
-    fn get_circular_index(&self, offset: isize) -> usize {
-        let new_index = offset;
-        if new_index >= 0 && new_index < self.circle.len() as isize {
-            new_index as usize
-        } else if new_index < 0 {
-            self.circle.len() - (new_index.abs() as usize)
-        } else {
-            (new_index as usize) - self.circle.len()
-        }
-    }
-
-    fn interleave(&mut self, interleave_index: usize, num_to_add: usize) {
-        self.circle = [
-            &self.circle[..interleave_index],
-            &(interleave_index..self.circle.len())
-                .map(|i| self.circle[i])
-                .interleave((0..num_to_add).map(|i| i + self.num_marbles_used))
-                .collect::<Vec<_>>(),
-        ]
-        .concat();
-        self.num_marbles_used += num_to_add;
-        self.current_marble_index = interleave_index + num_to_add * 2 - 1;
-        self.move_player(num_to_add + 1);
-    }
-
-    fn next_circle(&mut self) {
-        let interleave_index = self.get_circular_index(self.current_marble_index as isize + 1);
-        let num_to_add =
-            (self.circle.len() - interleave_index).min(self.num_marbles - self.num_marbles_used);
-        if let Some((index, marble)) = (0..num_to_add)
-            .map(|i| (i, self.num_marbles_used + i))
-            .find(|(_, m)| *m % 23 == 0)
-        {
-            self.interleave(interleave_index, index);
-
-            // player scores
-            let remove_marble_index =
-                self.get_circular_index(self.current_marble_index as isize - 7);
-            let remove_marble = self.circle[remove_marble_index];
-            self.circle = [
-                &self.circle[..remove_marble_index],
-                &self.circle[remove_marble_index + 1..],
-            ]
-            .concat();
-            self.add_score(marble + remove_marble);
-            self.num_marbles_used += 1;
-            self.current_marble_index = self.get_circular_index(remove_marble_index as isize);
-            self.move_player(1);
-        // continue
+pub fn play_game(num_players: usize, num_marbles: usize, record_history: bool) -> MarbleGame {
+    // the current marble is always kept at the back of the circle
+    let mut circle = VecDeque::with_capacity(num_marbles + 1);
+    circle.push_back(0);
+    let mut scores = vec![0; num_players];
+    let mut history = if record_history {
+        Some(Vec::with_capacity(num_marbles / 23))
+    } else {
+        None
+    };
+    for marble in 1..=num_marbles {
+        if marble % 23 == 0 {
+            circle.rotate_right(7);
+            let current_player = (marble - 1) % num_players;
+            scores[current_player] += marble + circle.pop_back().unwrap();
+            circle.rotate_left(1);
+            if let Some(history) = history.as_mut() {
+                history.push((marble, current_player, scores[current_player]));
+            }
         } else {
-            self.interleave(interleave_index, num_to_add);
-        }
-    }
-}
-
-fn get_max_score_2(num_players: usize, num_marbles: usize) -> usize {
-    let mut game = GameCircle::new(num_players, num_marbles);
-    loop {
-        //        println!("{:?}", game.circle);
-        if game.num_marbles_used >= num_marbles {
-            break;
+            circle.rotate_left(1);
+            circle.push_back(marble);
         }
-        game.next_circle()
     }
-    *game.scores.values().max().unwrap()
+    MarbleGame { scores, history }
 }
 
-#[allow(dead_code)]
 fn get_max_score(num_players: usize, num_marbles: usize) -> usize {
-    let mut circle = vec![0];
-    let mut current_marble_index = 0;
-    let mut current_player = 0;
-    let mut scores = HashMap::new();
-    for i in 1..=num_marbles {
-        current_player += 1;
-        if current_player >= num_players {
-            current_player = 0;
-        }
-        if i % 23 == 0 {
-            let remove_marble_index =
-                get_circular_index(circle.len(), current_marble_index as isize - 7);
-            let remove_marble = circle[remove_marble_index];
-            circle = [
-                &circle[..remove_marble_index],
-                &circle[remove_marble_index + 1..],
-            ]
-            .concat();
-            *scores.entry(current_player).or_insert(0) += i + remove_marble;
-            current_marble_index = get_circular_index(circle.len(), remove_marble_index as isize);
-        } else {
-            let insert_index = get_circular_index(circle.len(), current_marble_index as isize + 1);
-            circle = [
-                &circle[..=insert_index],
-                &[i],
-                &circle[(insert_index + 1)..],
-            ]
-            .concat();
-            current_marble_index = get_circular_index(circle.len(), insert_index as isize + 1);
-        }
-    }
-    *scores.values().max().unwrap()
+    *play_game(num_players, num_marbles, false)
+        .scores
+        .iter()
+        .max()
+        .unwrap()
 }
 
 #[aoc(day9, part1)]
@@ -0,0 +1,748 @@
+use chrono::{Duration, NaiveDate};
+use day16;
+use day19;
+use hashbrown::HashSet;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+// splitmix64, so the same seed gives the same inputs everywhere
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // uniform in low..high
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        assert!(low < high, "Empty range {}..{}", low, high);
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0, i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+pub fn day1(rng: &mut Rng, num_changes: usize, max_change: usize) -> String {
+    (0..num_changes)
+        .map(|_| {
+            let change = rng.range(1, max_change + 1);
+            if rng.chance(0.5) {
+                format!("+{}", change)
+            } else {
+                format!("-{}", change)
+            }
+        })
+        .join("\n")
+}
+
+// the last ID is a copy of another with one letter changed, so part 2 has an answer
+pub fn day2(rng: &mut Rng, num_ids: usize, length: usize) -> String {
+    let mut box_ids: Vec<Vec<u8>> = (0..num_ids - 1)
+        .map(|_| (0..length).map(|_| b'a' + rng.range(0, 26) as u8).collect())
+        .collect();
+    let mut near_duplicate = box_ids[rng.range(0, num_ids - 1)].clone();
+    let position = rng.range(0, length);
+    near_duplicate[position] =
+        b'a' + (near_duplicate[position] - b'a' + 1 + rng.range(0, 25) as u8) % 26;
+    box_ids.push(near_duplicate);
+    box_ids
+        .into_iter()
+        .map(|box_id| String::from_utf8(box_id).unwrap())
+        .join("\n")
+}
+
+// Every claim after the first covers a square of an earlier one, so they all overlap
+// something. One more claim is then put to the right of all of them so exactly one is intact.
+pub fn day3(rng: &mut Rng, num_claims: usize, fabric_size: usize, max_claim: usize) -> String {
+    assert!(num_claims >= 3, "Too few claims to overlap");
+    let mut claims: Vec<(usize, usize, usize, usize)> = Vec::with_capacity(num_claims);
+    for _ in 1..num_claims {
+        let (width, height) = (rng.range(1, max_claim + 1), rng.range(1, max_claim + 1));
+        let (x, y) = if claims.is_empty() {
+            (
+                rng.range(0, fabric_size.saturating_sub(width) + 1),
+                rng.range(0, fabric_size.saturating_sub(height) + 1),
+            )
+        } else {
+            let (other_x, other_y, other_width, other_height) = claims[rng.range(0, claims.len())];
+            let shared_x = rng.range(other_x, other_x + other_width);
+            let shared_y = rng.range(other_y, other_y + other_height);
+            (
+                rng.range((shared_x + 1).saturating_sub(width), shared_x + 1),
+                rng.range((shared_y + 1).saturating_sub(height), shared_y + 1),
+            )
+        };
+        claims.push((x, y, width, height));
+    }
+    let right = claims
+        .iter()
+        .map(|(x, _, width, _)| x + width)
+        .max()
+        .unwrap();
+    claims.push((
+        right + 1,
+        rng.range(0, fabric_size.max(1)),
+        rng.range(1, max_claim + 1),
+        rng.range(1, max_claim + 1),
+    ));
+    claims
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y, width, height))| format!("#{} @ {},{}: {}x{}", i + 1, x, y, width, height))
+        .join("\n")
+}
+
+// Shifts on consecutive nights, each starting just before or just after midnight with at least
+// one nap. The records come out shuffled like the puzzle's.
+pub fn day4(rng: &mut Rng, num_shifts: usize, num_guards: usize, max_naps: usize) -> String {
+    assert!((1..=27).contains(&max_naps), "Weird number of naps");
+    let mut guard_ids = HashSet::new();
+    while guard_ids.len() < num_guards {
+        guard_ids.insert(rng.range(1, 4000));
+    }
+    let guard_ids: Vec<_> = guard_ids.into_iter().sorted();
+    let first_night = NaiveDate::from_ymd_opt(1518, 3, 1).unwrap();
+    let mut records = Vec::new();
+    for night in 0..num_shifts {
+        let date = first_night + Duration::days(night as i64);
+        let begins = if rng.chance(0.5) {
+            (date - Duration::days(1))
+                .and_hms_opt(23, rng.range(45, 60) as u32, 0)
+                .unwrap()
+        } else {
+            date.and_hms_opt(0, rng.range(0, 5) as u32, 0).unwrap()
+        };
+        records.push((
+            begins,
+            format!(
+                "Guard #{} begins shift",
+                guard_ids[rng.range(0, guard_ids.len())]
+            ),
+        ));
+        let mut minutes: Vec<_> = (5..60).collect();
+        rng.shuffle(&mut minutes);
+        let num_naps = rng.range(1, max_naps + 1);
+        for (i, minute) in minutes
+            .into_iter()
+            .take(2 * num_naps)
+            .sorted()
+            .into_iter()
+            .enumerate()
+        {
+            let event = if i % 2 == 0 {
+                "falls asleep"
+            } else {
+                "wakes up"
+            };
+            records.push((date.and_hms_opt(0, minute, 0).unwrap(), event.to_string()));
+        }
+    }
+    rng.shuffle(&mut records);
+    records
+        .into_iter()
+        .map(|(time, event)| format!("[{}] {}", time.format("%Y-%m-%d %H:%M"), event))
+        .join("\n")
+}
+
+pub fn day5(rng: &mut Rng, length: usize, num_unit_types: usize) -> String {
+    (0..length)
+        .map(|_| {
+            let unit = (b'a' + rng.range(0, num_unit_types) as u8) as char;
+            if rng.chance(0.5) {
+                unit.to_ascii_uppercase()
+            } else {
+                unit
+            }
+        })
+        .collect()
+}
+
+pub fn day6(rng: &mut Rng, num_coordinates: usize, grid_size: usize) -> String {
+    let mut coordinates = HashSet::new();
+    while coordinates.len() < num_coordinates {
+        coordinates.insert((rng.range(0, grid_size), rng.range(0, grid_size)));
+    }
+    coordinates
+        .into_iter()
+        .map(|(x, y)| format!("{}, {}", x, y))
+        .sorted()
+        .join("\n")
+}
+
+// steps are put in a random order and only depend on earlier ones, every step after the
+// first depends on at least one so they all show up in the instructions
+pub fn day7(rng: &mut Rng, num_steps: usize, edge_probability: f64) -> String {
+    assert!((2..=26).contains(&num_steps), "Weird number of steps");
+    let mut steps: Vec<_> = (b'A'..=b'Z').take(num_steps).map(|c| c as char).collect();
+    rng.shuffle(&mut steps);
+    let mut edges = Vec::new();
+    for j in 1..num_steps {
+        let required = rng.range(0, j);
+        for i in 0..j {
+            if i == required || rng.chance(edge_probability) {
+                edges.push((steps[i], steps[j]));
+            }
+        }
+    }
+    rng.shuffle(&mut edges);
+    edges
+        .into_iter()
+        .map(|(a, b)| format!("Step {} must be finished before step {} can begin.", a, b))
+        .join("\n")
+}
+
+fn get_node(
+    rng: &mut Rng,
+    num_left: &mut usize,
+    max_children: usize,
+    max_metadata: usize,
+    numbers: &mut Vec<usize>,
+) {
+    let num_children = rng.range(0, max_children + 1).min(*num_left);
+    *num_left -= num_children;
+    let num_metadata = rng.range(1, max_metadata + 1);
+    numbers.push(num_children);
+    numbers.push(num_metadata);
+    for _ in 0..num_children {
+        get_node(rng, num_left, max_children, max_metadata, numbers);
+    }
+    // small enough that part 2 often finds the child a metadata entry points to
+    numbers.extend((0..num_metadata).map(|_| rng.range(1, max_children + 3)));
+}
+
+// a license tree with at most num_nodes nodes, handed out to children as the tree is built
+pub fn day8(rng: &mut Rng, num_nodes: usize, max_children: usize, max_metadata: usize) -> String {
+    assert!(num_nodes > 0 && max_metadata > 0, "Empty tree");
+    let mut num_left = num_nodes - 1;
+    let mut numbers = Vec::new();
+    get_node(rng, &mut num_left, max_children, max_metadata, &mut numbers);
+    numbers.into_iter().join(" ")
+}
+
+pub fn day9(rng: &mut Rng, max_players: usize, max_marbles: usize) -> String {
+    format!(
+        "{} players; last marble is worth {} points",
+        rng.range(1, max_players + 1),
+        rng.range(1, max_marbles + 1)
+    )
+}
+
+// random pixels in a width by height box that all line up after the given number of seconds
+pub fn day10(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    seconds: usize,
+    max_speed: usize,
+) -> String {
+    let mut pixels: Vec<_> = iproduct!(0..width, 0..height)
+        .filter(|_| rng.chance(0.5))
+        .collect();
+    if pixels.is_empty() {
+        pixels.push((0, 0));
+    }
+    let max_speed = max_speed as isize;
+    pixels
+        .into_iter()
+        .map(|(x, y)| {
+            let velocity = (
+                rng.range(0, 2 * max_speed as usize + 1) as isize - max_speed,
+                rng.range(0, 2 * max_speed as usize + 1) as isize - max_speed,
+            );
+            let position = (
+                x as isize - velocity.0 * seconds as isize,
+                y as isize - velocity.1 * seconds as isize,
+            );
+            format!(
+                "position=<{:>6}, {:>6}> velocity=<{:>2}, {:>2}>",
+                position.0, position.1, velocity.0, velocity.1
+            )
+        })
+        .sorted()
+        .join("\n")
+}
+
+pub fn day11(rng: &mut Rng) -> String {
+    rng.range(1, 10000).to_string()
+}
+
+// All 32 rules like the puzzle's, with "....." always dying so the pots can't fill up forever.
+// Random rules rarely settle into the steady drift part 2 relies on.
+pub fn day12(rng: &mut Rng, num_pots: usize) -> String {
+    let pots = |rng: &mut Rng, n: usize| -> String {
+        (0..n)
+            .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+            .collect()
+    };
+    let initial_state = pots(rng, num_pots);
+    let rules = (0..32)
+        .map(|rule: usize| {
+            let pattern: String = (0..5)
+                .map(|i| if rule & 1 << (4 - i) != 0 { '#' } else { '.' })
+                .collect();
+            let outcome = if rule == 0 {
+                ".".to_string()
+            } else {
+                pots(rng, 1)
+            };
+            format!("{} => {}", pattern, outcome)
+        })
+        .join("\n");
+    format!("initial state: {}\n\n{}", initial_state, rules)
+}
+
+// track pieces while the loops are being laid out
+#[derive(Clone, Copy, PartialEq)]
+enum Piece {
+    Empty,
+    Horizontal,
+    Vertical,
+    Corner(char),
+    Intersection,
+}
+
+// the cells of a rectangle in clockwise order, starting from the top left corner
+fn get_loop(top: usize, left: usize, bottom: usize, right: usize) -> Vec<(usize, usize, Piece)> {
+    let mut cells = vec![(top, left, Piece::Corner('/'))];
+    cells.extend((left + 1..right).map(|column| (top, column, Piece::Horizontal)));
+    cells.push((top, right, Piece::Corner('\\')));
+    cells.extend((top + 1..bottom).map(|row| (row, right, Piece::Vertical)));
+    cells.push((bottom, right, Piece::Corner('/')));
+    cells.extend(
+        (left + 1..right)
+            .rev()
+            .map(|column| (bottom, column, Piece::Horizontal)),
+    );
+    cells.push((bottom, left, Piece::Corner('\\')));
+    cells.extend(
+        (top + 1..bottom)
+            .rev()
+            .map(|row| (row, left, Piece::Vertical)),
+    );
+    cells
+}
+
+// Rectangular loops that only touch existing track where a straight piece crosses a
+// perpendicular one, anything else could join two loops in a way the carts can't follow.
+// The carts all go on the first loop, which nothing else crosses, with no two next to each
+// other and one more going clockwise than anticlockwise. Carts going the same way never catch
+// up with each other, and every crash takes out one going each way, so one is left for part 2.
+pub fn day13(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    num_loops: usize,
+    num_carts: usize,
+) -> String {
+    assert!(width >= 3 && height >= 3, "Grid too small for a loop");
+    assert!(num_carts % 2 == 1, "Need an odd number of carts");
+    let mut grid = vec![vec![Piece::Empty; width]; height];
+    let (top, left) = (
+        rng.range(0, (height - 3) / 4 + 1),
+        rng.range(0, (width - 3) / 4 + 1),
+    );
+    let (bottom, right) = (
+        height - 1 - rng.range(0, (height - 3) / 4 + 1),
+        width - 1 - rng.range(0, (width - 3) / 4 + 1),
+    );
+    let cart_loop = get_loop(top, left, bottom, right);
+    for (row, column, piece) in &cart_loop {
+        grid[*row][*column] = *piece;
+    }
+    let on_cart_loop: HashSet<_> = cart_loop.iter().map(|(r, c, _)| (*r, *c)).collect();
+    let mut num_placed = 1;
+    for _ in 0..num_loops * 100 {
+        if num_placed >= num_loops {
+            break;
+        }
+        let (top, left) = (rng.range(0, height - 2), rng.range(0, width - 2));
+        let (bottom, right) = (rng.range(top + 2, height), rng.range(left + 2, width));
+        let cells = get_loop(top, left, bottom, right);
+        let new_cells: HashSet<_> = cells.iter().map(|(r, c, _)| (*r, *c)).collect();
+        let fits = cells.iter().all(|(row, column, piece)| {
+            if on_cart_loop.contains(&(*row, *column)) {
+                return false;
+            }
+            let crossing = match (grid[*row][*column], piece) {
+                (Piece::Empty, _) => false,
+                (Piece::Horizontal, Piece::Vertical) | (Piece::Vertical, Piece::Horizontal) => true,
+                _ => return false,
+            };
+            let (row, column) = (*row as isize, *column as isize);
+            [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().all(|(dr, dc)| {
+                let (r, c) = (row + dr, column + dc);
+                if r < 0 || c < 0 || r >= height as isize || c >= width as isize {
+                    return true;
+                }
+                let (r, c) = (r as usize, c as usize);
+                // old track can only be next to the new loop where one runs through the other
+                let along_old_line = crossing
+                    && match piece {
+                        Piece::Horizontal => *dc == 0,
+                        _ => *dr == 0,
+                    };
+                let touches = !(grid[r][c] == Piece::Empty || along_old_line)
+                    || on_cart_loop.contains(&(r, c));
+                !touches || new_cells.contains(&(r, c))
+            })
+        });
+        if fits {
+            for (row, column, piece) in cells {
+                grid[row][column] = if grid[row][column] == Piece::Empty {
+                    piece
+                } else {
+                    Piece::Intersection
+                };
+            }
+            num_placed += 1;
+        }
+    }
+    let mut rows: Vec<Vec<char>> = grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|piece| match piece {
+                    Piece::Empty => ' ',
+                    Piece::Horizontal => '-',
+                    Piece::Vertical => '|',
+                    Piece::Corner(c) => *c,
+                    Piece::Intersection => '+',
+                })
+                .collect()
+        })
+        .collect();
+    let mut straights: Vec<_> = (0..cart_loop.len())
+        .filter(|i| !matches!(cart_loop[*i].2, Piece::Corner(_)))
+        .collect();
+    rng.shuffle(&mut straights);
+    let mut taken = vec![false; cart_loop.len()];
+    let mut positions = Vec::with_capacity(num_carts);
+    for i in straights {
+        let (before, after) = (
+            (i + cart_loop.len() - 1) % cart_loop.len(),
+            (i + 1) % cart_loop.len(),
+        );
+        if positions.len() < num_carts && !taken[before] && !taken[after] {
+            taken[i] = true;
+            positions.push(i);
+        }
+    }
+    assert!(positions.len() == num_carts, "No room for the carts");
+    let mut clockwise: Vec<_> = (0..num_carts).map(|i| i <= num_carts / 2).collect();
+    rng.shuffle(&mut clockwise);
+    for (i, clockwise) in positions.into_iter().zip(clockwise) {
+        let (row, column, piece) = cart_loop[i];
+        // clockwise is right along the top, down the right, left along the bottom, up the left
+        let forwards = match piece {
+            Piece::Horizontal => row == top,
+            _ => column == right,
+        } == clockwise;
+        rows[row][column] = match (piece, forwards) {
+            (Piece::Horizontal, true) => '>',
+            (Piece::Horizontal, false) => '<',
+            (_, true) => 'v',
+            (_, false) => '^',
+        };
+    }
+    rows.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .join("\n")
+}
+
+pub fn day14(rng: &mut Rng, num_digits: usize) -> String {
+    (0..num_digits)
+        .map(|_| (b'0' + rng.range(0, 10) as u8) as char)
+        .collect()
+}
+
+// open squares are carved out from the middle so every unit can reach every other one
+pub fn day15(
+    rng: &mut Rng,
+    size: usize,
+    num_open: usize,
+    num_goblins: usize,
+    num_elves: usize,
+) -> String {
+    assert!(size >= 3, "Cave too small");
+    let mut cave = vec![vec!['#'; size]; size];
+    let mut open = vec![(size / 2, size / 2)];
+    cave[size / 2][size / 2] = '.';
+    let num_open = num_open.min((size - 2) * (size - 2));
+    while open.len() < num_open {
+        let (row, column) = open[rng.range(0, open.len())];
+        let (r, c) = match rng.range(0, 4) {
+            0 => (row - 1, column),
+            1 => (row + 1, column),
+            2 => (row, column - 1),
+            _ => (row, column + 1),
+        };
+        if r > 0 && c > 0 && r < size - 1 && c < size - 1 && cave[r][c] == '#' {
+            cave[r][c] = '.';
+            open.push((r, c));
+        }
+    }
+    rng.shuffle(&mut open);
+    let units = (0..num_goblins)
+        .map(|_| 'G')
+        .chain((0..num_elves).map(|_| 'E'));
+    for ((row, column), unit) in open.into_iter().zip(units) {
+        cave[row][column] = unit;
+    }
+    cave.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .join("\n")
+}
+
+// Samples are added until every opcode number has one and they can all be worked out, and
+// the test program only uses instructions that keep the registers small enough not to overflow.
+pub fn day16(rng: &mut Rng, num_samples: usize, num_instructions: usize) -> String {
+    let mut opcodes: Vec<_> = day16::OpCode::iter().collect();
+    rng.shuffle(&mut opcodes);
+    let get_instruction = |rng: &mut Rng| {
+        format!(
+            "{} {} {} {}",
+            rng.range(0, 16),
+            rng.range(0, 4),
+            rng.range(0, 4),
+            rng.range(0, 4)
+        )
+    };
+    let format_registers = |registers: &[usize; 4]| registers.iter().join(", ");
+    let mut candidates = [0xffffu16; 16];
+    let mut sampled = [false; 16];
+    let mut samples = Vec::new();
+    while samples.len() < num_samples || sampled.contains(&false) || !is_resolved(candidates) {
+        let mut before = [0; 4];
+        for register in &mut before {
+            *register = rng.range(0, 4);
+        }
+        let line = get_instruction(rng);
+        let instruction = day16::Instruction::new(&line);
+        let number: usize = line.split(' ').next().unwrap().parse().unwrap();
+        let after = opcodes[number].execute(&instruction, &before);
+        sampled[number] = true;
+        candidates[number] &= opcodes
+            .iter()
+            .enumerate()
+            .filter(|(_, opcode)| opcode.execute(&instruction, &before) == after)
+            .fold(0, |set, (i, _)| set | 1 << i);
+        samples.push(format!(
+            "Before: [{}]\n{}\nAfter:  [{}]",
+            format_registers(&before),
+            line,
+            format_registers(&after)
+        ));
+    }
+    let mut registers = [0; 4];
+    let mut program = Vec::with_capacity(num_instructions);
+    while program.len() < num_instructions {
+        let line = get_instruction(rng);
+        let number: usize = line.split(' ').next().unwrap().parse().unwrap();
+        let next = opcodes[number].execute(&day16::Instruction::new(&line), &registers);
+        if next.iter().all(|register| *register < 1 << 20) {
+            registers = next;
+            program.push(line);
+        }
+    }
+    format!("{}\n\n\n\n{}", samples.join("\n\n"), program.join("\n"))
+}
+
+// candidates[n] is the set of opcodes number n could still be, numbers with only one left
+// rule it out for the others
+fn is_resolved(mut candidates: [u16; 16]) -> bool {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..16 {
+            if candidates[i].count_ones() != 1 {
+                continue;
+            }
+            for j in (0..16).filter(|j| *j != i) {
+                if candidates[j] & candidates[i] != 0 {
+                    candidates[j] &= !candidates[i];
+                    changed = true;
+                }
+            }
+        }
+    }
+    candidates.iter().all(|set| set.count_ones() == 1)
+}
+
+// buckets of clay around the spring at x=500, each a floor with a wall at both ends
+pub fn day17(rng: &mut Rng, num_buckets: usize, width: usize, depth: usize) -> String {
+    assert!((3..=1000).contains(&width) && depth >= 3, "Weird size");
+    let low = 500 - width / 2;
+    let mut veins = Vec::with_capacity(3 * num_buckets);
+    for _ in 0..num_buckets {
+        let left = rng.range(low, low + width - 2);
+        let right = rng.range(left + 2, low + width);
+        let top = rng.range(1, depth - 1);
+        let bottom = rng.range(top + 1, depth + 1);
+        veins.push(format!("x={}, y={}..{}", left, top, bottom));
+        veins.push(format!("x={}, y={}..{}", right, top, bottom));
+        veins.push(format!("y={}, x={}..{}", bottom, left, right));
+    }
+    rng.shuffle(&mut veins);
+    veins.join("\n")
+}
+
+pub fn day18(rng: &mut Rng, width: usize, height: usize) -> String {
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| match rng.range(0, 3) {
+                    0 => '.',
+                    1 => '|',
+                    _ => '#',
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+const MNEMONICS: [&str; 16] = [
+    "addr", "addi", "mulr", "muli", "banr", "bani", "borr", "bori", "setr", "seti", "gtir", "gtri",
+    "gtrr", "eqir", "eqri", "eqrr",
+];
+
+// Nothing writes to the instruction pointer's register, so the program runs straight through
+// once, and instructions that would let a register grow too big are skipped.
+pub fn day19(rng: &mut Rng, num_instructions: usize) -> String {
+    let ip_register = rng.range(0, 6);
+    let mut registers = [0; 6];
+    let mut program = Vec::with_capacity(num_instructions);
+    while program.len() < num_instructions {
+        let output = rng.range(0, 6);
+        if output == ip_register {
+            continue;
+        }
+        let line = format!(
+            "{} {} {} {}",
+            MNEMONICS[rng.range(0, 16)],
+            rng.range(0, 6),
+            rng.range(0, 6),
+            output
+        );
+        let instruction = day19::Instruction::new(&line);
+        registers[ip_register] = program.len();
+        let next = instruction.opcode.execute(&instruction, &registers);
+        if next.iter().all(|register| *register < 1 << 20) {
+            registers = next;
+            program.push(line);
+        }
+    }
+    format!("#ip {}\n{}", ip_register, program.join("\n"))
+}
+
+pub fn day22(rng: &mut Rng, max_depth: usize, max_target: usize) -> String {
+    format!(
+        "depth: {}\ntarget: {},{}",
+        rng.range(1, max_depth + 1),
+        rng.range(0, max_target + 1),
+        rng.range(0, max_target + 1)
+    )
+}
+
+pub fn day23(
+    rng: &mut Rng,
+    num_nanobots: usize,
+    max_coordinate: usize,
+    max_radius: usize,
+) -> String {
+    let mut coordinate = || rng.range(0, 2 * max_coordinate + 1) as isize - max_coordinate as isize;
+    let positions: Vec<_> = (0..num_nanobots)
+        .map(|_| (coordinate(), coordinate(), coordinate()))
+        .collect();
+    positions
+        .into_iter()
+        .map(|(x, y, z)| {
+            format!(
+                "pos=<{},{},{}>, r={}",
+                x,
+                y,
+                z,
+                rng.range(1, max_radius + 1)
+            )
+        })
+        .join("\n")
+}
+
+const ATTACK_TYPES: [&str; 5] = ["bludgeoning", "cold", "fire", "radiation", "slashing"];
+
+fn get_group(rng: &mut Rng, initiative: usize) -> String {
+    let mut attack_types = ATTACK_TYPES.to_vec();
+    rng.shuffle(&mut attack_types);
+    let num_weak = rng.range(0, 3);
+    let num_immune = rng.range(0, 3);
+    let mut modifiers = Vec::new();
+    if num_weak > 0 {
+        modifiers.push(format!("weak to {}", attack_types[..num_weak].join(", ")));
+    }
+    if num_immune > 0 {
+        modifiers.push(format!(
+            "immune to {}",
+            attack_types[num_weak..num_weak + num_immune].join(", ")
+        ));
+    }
+    rng.shuffle(&mut modifiers);
+    let modifiers = if modifiers.is_empty() {
+        String::new()
+    } else {
+        format!("({}) ", modifiers.join("; "))
+    };
+    format!(
+        "{} units each with {} hit points {}with an attack that does {} {} damage at initiative {}",
+        rng.range(1, 5000),
+        rng.range(1, 12000),
+        modifiers,
+        rng.range(2, 200),
+        ATTACK_TYPES[rng.range(0, ATTACK_TYPES.len())],
+        initiative
+    )
+}
+
+// initiatives are a shuffle of 1..=groups so no two groups tie
+pub fn day24(
+    rng: &mut Rng,
+    num_immune_system_groups: usize,
+    num_infection_groups: usize,
+) -> String {
+    let mut initiatives: Vec<_> = (1..=num_immune_system_groups + num_infection_groups).collect();
+    rng.shuffle(&mut initiatives);
+    let (immune_system, infection) = initiatives.split_at(num_immune_system_groups);
+    let immune_system = immune_system.iter().map(|i| get_group(rng, *i)).join("\n");
+    let infection = infection.iter().map(|i| get_group(rng, *i)).join("\n");
+    format!(
+        "Immune System:\n{}\n\nInfection:\n{}",
+        immune_system, infection
+    )
+}
+
+pub fn day25(rng: &mut Rng, num_points: usize, max_coordinate: usize) -> String {
+    (0..num_points)
+        .map(|_| {
+            (0..4)
+                .map(|_| rng.range(0, 2 * max_coordinate + 1) as isize - max_coordinate as isize)
+                .join(",")
+        })
+        .join("\n")
+}
@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+pub type Colour = [u8; 3];
+
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Colour>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![[0, 0, 0]; width * height],
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Colour) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Colour {
+        self.pixels[y * self.width + x]
+    }
+
+    // binary PPM (P6), which most image tools can convert to PNG
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            bytes.extend_from_slice(pixel);
+        }
+        bytes
+    }
+
+    pub fn write_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        File::create(path)?.write_all(&self.to_ppm())
+    }
+}
+
+// spreads hues around the colour wheel with the golden ratio so neighbouring labels differ
+pub fn label_colour(label: usize) -> Colour {
+    let hue = (label as f64 * 0.618_033_988_75).fract() * 6.;
+    let (sector, offset) = (hue.floor() as usize, hue.fract());
+    let (high, low) = (230., 70.);
+    let (rising, falling) = (low + (high - low) * offset, high - (high - low) * offset);
+    let (r, g, b) = match sector {
+        0 => (high, rising, low),
+        1 => (falling, high, low),
+        2 => (low, high, rising),
+        3 => (low, falling, high),
+        4 => (rising, low, high),
+        _ => (high, low, falling),
+    };
+    [r as u8, g as u8, b as u8]
+}
+
+// black through red and yellow to white
+pub fn heat_colour(value: usize, max_value: usize) -> Colour {
+    let heat = value as f64 / max_value.max(1) as f64 * 3.;
+    let channel = |start: f64| ((heat - start).clamp(0., 1.) * 255.) as u8;
+    [channel(0.), channel(1.), channel(2.)]
+}
@@ -14,6 +14,7 @@ extern crate strum;
 #[macro_use]
 extern crate strum_macros;
 
+pub mod cycle;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -37,5 +38,7 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod generators;
+pub mod image;
 
 aoc_lib! { year = 2018 }